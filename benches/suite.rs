@@ -0,0 +1,214 @@
+//! Benchmarks for the strategies, the logical solver, the brute-force solvers and the generator.
+//!
+//! Run with `cargo bench`. Any further arguments after `--` are filters, so that only benchmarks
+//! whose names contain one of them are run - for example `cargo bench -- chain msls`. Pass
+//! `--save-baseline <file>` to record the results, and `--baseline <file>` to compare against
+//! results recorded earlier.
+//!
+//! Each benchmark is run repeatedly until enough time has passed, and the median, fastest and
+//! slowest runs are reported per item - per position, per puzzle or per generated puzzle.
+
+extern crate rustdoku;
+
+use rustdoku::generator;
+use rustdoku::generator::{Backend, Symmetry};
+use rustdoku::grid::Grid;
+use rustdoku::grid::variants::{classic_from_string, empty_classic, Grid9};
+use rustdoku::solver;
+use rustdoku::solver::SolveConfiguration;
+use rustdoku::strategies::ALL_STRATEGIES;
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::process;
+use std::time::{Duration, Instant};
+
+/// How long to keep sampling each benchmark for.
+const MEASUREMENT_TIME: Duration = Duration::from_secs(3);
+
+/// The most samples to take of each benchmark.
+const MAX_SAMPLES: usize = 50;
+
+/// The number of positions on which each strategy is timed.
+const CORPUS_SIZE: usize = 200;
+
+/// The number of puzzles solved, and generated, in each sample.
+const NUM_PUZZLES: usize = 20;
+
+/// The timings of a benchmark, in nanoseconds per item.
+struct Timings {
+    median: f64,
+    fastest: f64,
+    slowest: f64,
+}
+
+struct Bencher {
+    filters: Vec<String>,
+    baseline: HashMap<String, f64>,
+    results: Vec<(String, Timings)>,
+}
+
+impl Bencher {
+
+    /// Whether the benchmark with the given name passes the filters.
+    fn wants(&self, name: &str) -> bool {
+        self.filters.is_empty() || self.filters.iter().any(|filter| name.contains(filter.as_str()))
+    }
+
+    /// Time the routine, which returns the number of items it dealt with, and report the result
+    /// unless the benchmark has been filtered out.
+    fn bench<F: FnMut() -> usize>(&mut self, name: &str, mut routine: F) {
+
+        if !self.wants(name) {
+            return;
+        }
+
+        // The first run tells how many items there are. It counts as a sample too, so that the
+        // slowest benchmarks are only run once.
+        let start = Instant::now();
+        let items = routine().max(1) as f64;
+        let mut samples = vec![start.elapsed().as_nanos() as f64 / items];
+        while start.elapsed() < MEASUREMENT_TIME && samples.len() < MAX_SAMPLES {
+            let sample_start = Instant::now();
+            routine();
+            samples.push(sample_start.elapsed().as_nanos() as f64 / items);
+        }
+        samples.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        let timings = Timings { median: samples[samples.len() / 2], fastest: samples[0], slowest: samples[samples.len() - 1] };
+        let change = match self.baseline.get(name) {
+            Some(&previous) => format!("  {:+.1}%", (timings.median / previous - 1.0) * 100.0),
+            None => String::new(),
+        };
+        println!("{:<46} {:>12} [{} .. {}]{}", name, format_time(timings.median), format_time(timings.fastest), format_time(timings.slowest), change);
+        self.results.push((name.to_string(), timings));
+    }
+}
+
+fn main() {
+
+    // Cargo passes `--bench` to benchmarks, which needs to be skipped.
+    let mut filters = Vec::new();
+    let mut save_baseline = None;
+    let mut baseline = HashMap::new();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--bench" => {},
+            "--save-baseline" => save_baseline = Some(args.next().unwrap_or_else(|| usage())),
+            "--baseline" => baseline = read_baseline(&args.next().unwrap_or_else(|| usage())),
+            other if other.starts_with("--") => usage(),
+            other => filters.push(other.to_string()),
+        }
+    }
+
+    let puzzles = read_puzzles();
+    let mut bencher = Bencher { filters, baseline, results: Vec::new() };
+
+    // Each strategy looks for its first step with any deductions in each position, as the solver
+    // would. Finding the positions takes a while, so it's skipped if no strategy is wanted.
+    let names: Vec<String> = ALL_STRATEGIES.iter().map(|strategy| format!("strategy/{}", strategy)).collect();
+    let corpus = if names.iter().any(|name| bencher.wants(name)) { positions(&puzzles) } else { Vec::new() };
+    for &strategy in ALL_STRATEGIES {
+        bencher.bench(&format!("strategy/{}", strategy), || {
+            for grid in &corpus {
+                strategy.find_steps(grid).map(|step| step.get_deductions(grid)).find(|deductions| !deductions.is_empty());
+            }
+            corpus.len()
+        });
+    }
+
+    let sample: Vec<&Grid<Grid9>> = puzzles.iter().step_by((puzzles.len() / NUM_PUZZLES).max(1)).collect();
+    let config = SolveConfiguration::with_all_strategies();
+    bencher.bench("solve/all-strategies", || {
+        for puzzle in &sample {
+            solver::solve(&mut (*puzzle).clone(), &config);
+        }
+        sample.len()
+    });
+
+    for &(name, backend) in &[("bitmask", Backend::Bitmask), ("dancing-links", Backend::DancingLinks)] {
+        let mut brute_force = backend.for_empty_grid(&empty_classic(), generator::seeded_rng(1));
+        let clues: Vec<Vec<usize>> = puzzles.iter().map(|puzzle| puzzle.to_clues()).collect();
+        bencher.bench(&format!("brute-force/{}/has-unique-solution", name), || {
+            for puzzle in &clues {
+                brute_force.has_unique_solution(puzzle);
+            }
+            clues.len()
+        });
+    }
+
+    bencher.bench("generator/symmetry", || {
+        generator::generate_puzzles_on_empty_grid_with_symmetry(empty_classic(), Symmetry::Rotational, false, generator::seeded_rng(1)).take(NUM_PUZZLES).count()
+    });
+    let pattern = vec![3, 11, 13, 19, 21, 23, 27, 29, 31, 33, 37, 39, 41, 43, 47, 49, 51, 53, 57, 59, 61, 67, 69, 77];
+    bencher.bench("generator/pattern", || {
+        generator::generate_puzzles_on_empty_grid_with_pattern(empty_classic(), pattern.clone(), generator::seeded_rng(1)).take(NUM_PUZZLES).count()
+    });
+
+    if let Some(path) = save_baseline {
+        let lines: Vec<String> = bencher.results.iter().map(|(name, timings)| format!("{} {}", name, timings.median)).collect();
+        if let Err(err) = fs::write(&path, lines.join("\n") + "\n") {
+            eprintln!("Couldn't save the baseline to {}: {}", path, err);
+            process::exit(1);
+        }
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("Usage: cargo bench -- [--save-baseline <file>] [--baseline <file>] [<filter>...]");
+    process::exit(2);
+}
+
+/// Read the puzzles from `classic_grids.txt`.
+fn read_puzzles() -> Vec<Grid<Grid9>> {
+    fs::read_to_string("classic_grids.txt").unwrap().lines()
+        .filter(|line| !line.is_empty() && !line.starts_with("//"))
+        .map(|line| classic_from_string(line.to_string()).unwrap())
+        .filter(|grid| !grid.is_solved())
+        .collect()
+}
+
+/// Positions met along the way when solving the puzzles, spread evenly over all of them.
+fn positions(puzzles: &[Grid<Grid9>]) -> Vec<Grid<Grid9>> {
+    let config = SolveConfiguration::with_all_strategies();
+    let mut positions = Vec::new();
+    for puzzle in puzzles {
+        let mut grid = puzzle.clone();
+        for (_, deductions) in solver::solve(&mut puzzle.clone(), &config).steps {
+            positions.push(grid.clone());
+            for deduction in deductions {
+                grid.apply_deduction(deduction);
+            }
+        }
+    }
+    let step = (positions.len() / CORPUS_SIZE).max(1);
+    positions.into_iter().step_by(step).take(CORPUS_SIZE).collect()
+}
+
+/// Read the medians saved by an earlier run.
+fn read_baseline(path: &str) -> HashMap<String, f64> {
+    let contents = fs::read_to_string(path).unwrap_or_else(|err| {
+        eprintln!("Couldn't read the baseline from {}: {}", path, err);
+        process::exit(1);
+    });
+    contents.lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            Some((parts.next()?.to_string(), parts.next()?.parse().ok()?))
+        })
+        .collect()
+}
+
+fn format_time(nanos: f64) -> String {
+    if nanos < 1e3 {
+        format!("{:.1} ns", nanos)
+    } else if nanos < 1e6 {
+        format!("{:.2} us", nanos / 1e3)
+    } else if nanos < 1e9 {
+        format!("{:.2} ms", nanos / 1e6)
+    } else {
+        format!("{:.2} s", nanos / 1e9)
+    }
+}
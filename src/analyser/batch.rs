@@ -0,0 +1,208 @@
+//! Solve and rate many puzzles at once, spreading the work across several threads.
+
+use analyser::rating::{rate_solve, Rating};
+use grid::{Grid, GridSize};
+use solver;
+use solver::{SolveConfiguration, SolveDetails, SolveResult};
+use strategies::Strategy;
+
+use std::collections::HashMap;
+use std::sync::{mpsc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The outcome of solving a single puzzle from a batch.
+pub struct PuzzleReport<T: GridSize> {
+    /// The puzzle as it was given.
+    pub puzzle: Grid<T>,
+    /// The grid as it was left at the end of the solve.
+    pub grid: Grid<T>,
+    /// The details of the solve.
+    pub details: SolveDetails<T>,
+    /// The rating of the puzzle, if it was solved.
+    pub rating: Option<Rating>,
+    /// The time taken to solve the puzzle.
+    pub duration: Duration,
+}
+
+/// Statistics about the solves of a whole batch of puzzles.
+#[derive(Default)]
+pub struct BatchSummary {
+    /// The number of puzzles in the batch.
+    pub num_puzzles: usize,
+    /// The number of puzzles which were solved.
+    pub solved: usize,
+    /// The number of puzzles which were found to be in contradiction.
+    pub contradiction: usize,
+    /// The number of puzzles which couldn't be solved with the available strategies.
+    pub insufficient_strategies: usize,
+    /// For each strategy which was used, the number of puzzles that needed it and the total number
+    /// of steps taken with it, in configuration order.
+    pub strategy_usage: Vec<(Strategy, usize, usize)>,
+    /// For each strategy, the number of solved puzzles for which it was the hardest strategy
+    /// needed, in configuration order.
+    pub hardest_strategies: Vec<(Strategy, usize)>,
+    /// The total time spent solving, summed over all puzzles.
+    pub total_time: Duration,
+    /// The longest time spent solving any single puzzle.
+    pub max_time: Duration,
+}
+
+/// Solve and rate each of the given puzzles, using the given number of threads. The reports are
+/// returned in the same order as the puzzles.
+pub fn solve_batch<T: GridSize>(puzzles: &[Grid<T>], configuration: &SolveConfiguration, num_threads: usize) -> Vec<PuzzleReport<T>> {
+    let mut reports = Vec::with_capacity(puzzles.len());
+    solve_stream(puzzles.iter().cloned().map(Ok::<_, ()>), configuration, num_threads.min(puzzles.len()), |report| {
+        reports.extend(report.ok());
+        true
+    });
+    reports
+}
+
+/// Solve and rate the puzzles as they arrive, using the given number of threads, and hand each
+/// report to `on_report` as soon as it and the reports of all earlier puzzles are ready. Items
+/// which are errors rather than puzzles are handed on in their place. Stops early once
+/// `on_report` returns `false`.
+pub fn solve_stream<T, E, I, F>(puzzles: I, configuration: &SolveConfiguration, num_threads: usize, mut on_report: F)
+    where T: GridSize, E: Send, I: Iterator<Item = Result<Grid<T>, E>> + Send, F: FnMut(Result<PuzzleReport<T>, E>) -> bool {
+
+    let (puzzles, stopped) = (&Mutex::new(puzzles.enumerate()), &AtomicBool::new(false));
+    let (sender, receiver) = mpsc::channel();
+
+    // Each worker repeatedly claims the next puzzle, so that the work stays evenly spread even
+    // when some puzzles take much longer than others.
+    thread::scope(|scope| {
+        for _ in 0..num_threads.max(1) {
+            let sender = sender.clone();
+            scope.spawn(move || while !stopped.load(Ordering::Relaxed) {
+                let next = puzzles.lock().unwrap().next();
+                let (idx, puzzle) = match next {
+                    Some(next) => next,
+                    None => break,
+                };
+                if sender.send((idx, puzzle.map(|puzzle| solve_puzzle(&puzzle, configuration)))).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(sender);
+
+        // Reports can arrive out of order, so each is held back until the ones before it are in.
+        let (mut pending, mut next_idx) = (HashMap::new(), 0);
+        for (idx, report) in receiver {
+            pending.insert(idx, report);
+            while let Some(report) = pending.remove(&next_idx) {
+                next_idx += 1;
+                if !on_report(report) {
+                    stopped.store(true, Ordering::Relaxed);
+                    return;
+                }
+            }
+        }
+    });
+}
+
+/// Solve and rate a single puzzle, timing how long it takes.
+pub fn solve_puzzle<T: GridSize>(puzzle: &Grid<T>, configuration: &SolveConfiguration) -> PuzzleReport<T> {
+    let start = Instant::now();
+    let mut grid = puzzle.clone();
+    let details = solver::solve(&mut grid, configuration);
+    let rating = rate_solve(&details, configuration);
+    PuzzleReport { puzzle: puzzle.clone(), grid, details, rating, duration: start.elapsed() }
+}
+
+impl BatchSummary {
+
+    /// Gather up statistics from the reports of a batch solved with the given configuration.
+    pub fn from_reports<T: GridSize>(reports: &[PuzzleReport<T>], configuration: &SolveConfiguration) -> BatchSummary {
+        let mut summary = BatchSummary::default();
+        for report in reports {
+            summary.add_report(report, configuration);
+        }
+        summary
+    }
+
+    /// Add the report of one more puzzle, solved with the given configuration, to the statistics.
+    pub fn add_report<T: GridSize>(&mut self, report: &PuzzleReport<T>, configuration: &SolveConfiguration) {
+
+        let strategies = configuration.strategies();
+        let order = |strategy: Strategy| strategies.iter().position(|&other| other == strategy);
+
+        let mut used: Vec<Strategy> = Vec::new();
+        for (step, _) in &report.details.steps {
+            let strategy = step.get_strategy();
+            if order(strategy).is_none() {
+                continue;
+            }
+            match self.strategy_usage.iter().position(|&(other, _, _)| other == strategy) {
+                Some(idx) => self.strategy_usage[idx].2 += 1,
+                None => self.strategy_usage.push((strategy, 0, 1)),
+            }
+            if !used.contains(&strategy) {
+                used.push(strategy);
+            }
+        }
+        for (strategy, puzzles, _) in self.strategy_usage.iter_mut() {
+            if used.contains(strategy) {
+                *puzzles += 1;
+            }
+        }
+        if let Some(Rating { score, .. }) = report.rating {
+            if score > 0 {
+                let strategy = strategies[score - 1];
+                match self.hardest_strategies.iter().position(|&(other, _)| other == strategy) {
+                    Some(idx) => self.hardest_strategies[idx].1 += 1,
+                    None => self.hardest_strategies.push((strategy, 1)),
+                }
+            }
+        }
+        self.strategy_usage.sort_by_key(|&(strategy, _, _)| order(strategy));
+        self.hardest_strategies.sort_by_key(|&(strategy, _)| order(strategy));
+
+        self.num_puzzles += 1;
+        match report.details.result {
+            SolveResult::Solved => self.solved += 1,
+            SolveResult::Contradiction => self.contradiction += 1,
+            SolveResult::InsufficientStrategies => self.insufficient_strategies += 1,
+        }
+        self.total_time += report.duration;
+        self.max_time = self.max_time.max(report.duration);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use grid::variants::{classic_from_string, Grid9};
+    use strategies::Step;
+    use super::*;
+
+    fn assert_send<S: Send>() {}
+
+    #[test]
+    fn test_grids_and_steps_are_send() {
+        assert_send::<Grid<Grid9>>();
+        assert_send::<Step<Grid9>>();
+        assert_send::<SolveDetails<Grid9>>();
+    }
+
+    #[test]
+    fn test_batch_keeps_input_order() {
+        let puzzles: Vec<_> = [
+            ".9.7..4..1..6...7.6...3..8.85...6.....6...3.....4...58.4..2...6.3...1..2..2..3.9.",
+            "...9.73...8..65.4.........9.6..4..151.......254..9..8.2.........9.25..7...64.8...",
+            ".....4..9..9....2.172..9..5......8..3...7...6..5......4..5..698.9....7..6..3.....",
+        ].iter().map(|line| classic_from_string(line.to_string()).unwrap()).collect();
+
+        let configuration = SolveConfiguration::with_all_strategies();
+        let reports = solve_batch(&puzzles, &configuration, 2);
+
+        for (puzzle, report) in puzzles.iter().zip(reports.iter()) {
+            let mut grid = puzzle.clone();
+            solver::solve(&mut grid, &configuration);
+            assert_eq!(report.grid.to_line_string(), grid.to_line_string());
+        }
+        assert_eq!(BatchSummary::from_reports(&reports, &configuration).solved, 3);
+    }
+}
@@ -1,5 +1,6 @@
 //! Analyse the solution path of a particular Sudoku, and check constraints.
 
+use analyser::{rate_solve, Rating};
 use grid::{Grid, GridSize};
 use solver;
 use solver::{SolveConfiguration, SolveDetails};
@@ -8,7 +9,7 @@ use solver::SolveResult::Solved;
 /// A group of constraints that must be met by a puzzle
 pub struct Criteria<'a, T: GridSize> {
     configuration: SolveConfiguration,
-    constraints: Vec<&'a dyn Fn(&SolveDetails<T>) -> bool>,
+    constraints: Vec<&'a (dyn Fn(&SolveDetails<T>) -> bool + Sync)>,
 }
 
 impl <'a, T: GridSize> Criteria<'a, T> {
@@ -23,8 +24,18 @@ impl <'a, T: GridSize> Criteria<'a, T> {
 }
 
 pub fn meets_criteria<T: GridSize>(grid: &Grid<T>, criteria: &Criteria<T>) -> bool {
+    rate_if_meets_criteria(grid, criteria).is_some()
+}
+
+/// Check whether the puzzle meets the criteria, and if it does then rate it with the strategies of
+/// the criteria. The rating is `None` for puzzles which meet the criteria without being solvable.
+pub fn rate_if_meets_criteria<T: GridSize>(grid: &Grid<T>, criteria: &Criteria<T>) -> Option<Option<Rating>> {
     let solve_details = solver::solve(&mut grid.clone(), &criteria.configuration);
-    criteria.constraints.iter().all(|con| con(&solve_details))
+    if criteria.constraints.iter().all(|con| con(&solve_details)) {
+        Some(rate_solve(&solve_details, &criteria.configuration))
+    } else {
+        None
+    }
 }
 
 fn solvable<T: GridSize>(solve_details: &SolveDetails<T>) -> bool {
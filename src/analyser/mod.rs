@@ -1,5 +1,11 @@
+mod batch;
 mod criteria;
+mod rating;
+mod repair;
 mod steps;
 
+pub use analyser::batch::*;
 pub use analyser::criteria::*;
+pub use analyser::rating::*;
+pub use analyser::repair::*;
 pub use analyser::steps::*;
\ No newline at end of file
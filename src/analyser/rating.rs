@@ -0,0 +1,46 @@
+//! Rate the difficulty of a puzzle by the hardest strategy needed to solve it.
+
+use grid::{Grid, GridSize};
+use solver;
+use solver::{SolveConfiguration, SolveDetails};
+use solver::SolveResult::Solved;
+use strategies::Strategy;
+
+/// The difficulty of a puzzle, as judged by the strategies needed to solve it.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Rating {
+    /// The position of the hardest strategy needed within the solve configuration, counting from
+    /// one - so a puzzle solvable with only the first strategy has a score of 1.
+    pub score: usize,
+    /// The hardest strategy needed to solve the puzzle, if any strategies were needed at all.
+    pub hardest_strategy: Option<Strategy>,
+    /// The number of steps taken to solve the puzzle.
+    pub num_steps: usize,
+}
+
+/// Rate the given puzzle, using the strategies of the given configuration in order of increasing
+/// difficulty. Returns `None` if the puzzle can't be solved with those strategies.
+pub fn rate<T: GridSize>(grid: &Grid<T>, configuration: &SolveConfiguration) -> Option<Rating> {
+    rate_solve(&solver::solve(&mut grid.clone(), configuration), configuration)
+}
+
+/// Rate a puzzle from the details of a solve that has already been carried out with the given
+/// configuration.
+pub fn rate_solve<T: GridSize>(solve_details: &SolveDetails<T>, configuration: &SolveConfiguration) -> Option<Rating> {
+
+    if solve_details.result != Solved {
+        return None;
+    }
+
+    let strategies = configuration.strategies();
+    let score = solve_details.steps.iter()
+        .filter_map(|(step, _)| strategies.iter().position(|&strategy| strategy == step.get_strategy()))
+        .max()
+        .map_or(0, |idx| idx + 1);
+
+    Some(Rating {
+        score,
+        hardest_strategy: if score > 0 { Some(strategies[score - 1]) } else { None },
+        num_steps: solve_details.steps.len(),
+    })
+}
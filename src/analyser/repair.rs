@@ -0,0 +1,143 @@
+//! Help for setters whose clues don't quite make a proper puzzle - suggesting clues to add to a
+//! puzzle with several solutions, and finding clues which a unique puzzle doesn't need.
+
+use rand::Rng;
+
+use analyser::rating::{rate, Rating};
+use analyser::steps::steps_to_solve;
+use generator::brute_force::{Backend, BruteForce};
+use grid::{Grid, GridSize};
+use grid::variants::grid_from_empty_grid_and_clues;
+use solver::SolveConfiguration;
+
+/// A set of clues which together make a puzzle unique, and what the resulting puzzle is like.
+#[derive(Clone, Debug)]
+pub struct ClueAddition {
+    /// The clues to add, as pairs of cell and digit.
+    pub clues: Vec<(usize, usize)>,
+    /// The rating of the resulting puzzle, if it can be solved with the configuration used.
+    pub rating: Option<Rating>,
+    /// The number of steps taken with each strategy of the configuration to solve the resulting
+    /// puzzle, if it can be solved.
+    pub steps: Option<Vec<usize>>,
+}
+
+/// The most solutions counted when choosing between clues to add, beyond which clues are thought
+/// of as equally poor.
+const MAX_COUNTED_SOLUTIONS: usize = 1000;
+
+/// Suggest ways to make the puzzle with the given clues on the given empty grid unique, searching
+/// with the given backend and random number generator, and rating each resulting puzzle with the
+/// given configuration. The fewest clues are suggested first, and then the easiest puzzles.
+///
+/// Only cells where two solutions differ need another clue, so each digit such a cell takes in
+/// some solution is tried in turn. If that isn't enough on its own, clues are added greedily,
+/// each time choosing the one which leaves the fewest solutions.
+pub fn suggest_clue_additions<T: GridSize, R: Rng>(empty_grid: &Grid<T>, clues: &[usize], configuration: &SolveConfiguration, backend: Backend, rng: R) -> Vec<ClueAddition> {
+
+    let mut solver = backend.for_empty_grid(empty_grid, rng);
+    let mut additions: Vec<Vec<(usize, usize)>> = Vec::new();
+
+    for first_clue in candidate_clues(&mut *solver, clues) {
+        let mut added = vec![first_clue];
+        let mut puzzle = clues.to_vec();
+        puzzle[first_clue.0] = first_clue.1;
+        while !solver.has_unique_solution(&puzzle) {
+            let (cell, digit) = candidate_clues(&mut *solver, &puzzle).into_iter()
+                .min_by_key(|&(cell, digit)| {
+                    let mut trial = puzzle.clone();
+                    trial[cell] = digit;
+                    solver.solutions(&trial, MAX_COUNTED_SOLUTIONS).count()
+                })
+                .unwrap();
+            puzzle[cell] = digit;
+            added.push((cell, digit));
+        }
+        added.sort();
+        if !additions.contains(&added) {
+            additions.push(added);
+        }
+    }
+
+    let strategy_groups: Vec<_> = configuration.strategies().iter().map(|&strategy| vec![strategy]).collect();
+    let mut suggestions: Vec<ClueAddition> = additions.into_iter().map(|added| {
+        let mut puzzle = clues.to_vec();
+        for &(cell, digit) in &added {
+            puzzle[cell] = digit;
+        }
+        let grid = grid_from_empty_grid_and_clues(empty_grid, &puzzle).unwrap();
+        ClueAddition { clues: added, rating: rate(&grid, configuration), steps: steps_to_solve(&grid, &strategy_groups) }
+    }).collect();
+
+    suggestions.sort_by_key(|suggestion| (suggestion.clues.len(), suggestion.rating.map_or(usize::MAX, |rating| rating.score)));
+    suggestions
+}
+
+/// Find the clues of the unique puzzle with the given clues on the given empty grid which could
+/// each be removed without losing uniqueness, searching with the given backend and random number
+/// generator. Removing several of them at once may not keep the puzzle unique.
+pub fn redundant_clues<T: GridSize, R: Rng>(empty_grid: &Grid<T>, clues: &[usize], backend: Backend, rng: R) -> Vec<usize> {
+    let mut solver = backend.for_empty_grid(empty_grid, rng);
+    let mut reduced = clues.to_vec();
+    (0..clues.len()).filter(|&cell| clues[cell] != 0).filter(|&cell| {
+        reduced[cell] = 0;
+        let unique = solver.has_unique_solution(&reduced);
+        reduced[cell] = clues[cell];
+        unique
+    }).collect()
+}
+
+/// The clues which would rule out at least one of two different solutions of the puzzle - a digit
+/// from any solution in a cell where the two solutions differ.
+fn candidate_clues(solver: &mut dyn BruteForce, clues: &[usize]) -> Vec<(usize, usize)> {
+    let solutions: Vec<Vec<usize>> = solver.solutions(clues, 2).collect();
+    if solutions.len() < 2 {
+        return Vec::new();
+    }
+    let backbone = solver.backbone(clues);
+    (0..clues.len())
+        .filter(|&cell| solutions[0][cell] != solutions[1][cell])
+        .flat_map(|cell| backbone[cell].iter().map(move |&digit| (cell, digit)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use generator::brute_force::BruteForceSolver;
+    use generator::seeded_rng;
+    use grid::variants::{classic_from_string, empty_classic};
+    use super::*;
+
+    #[test]
+    fn test_repair_and_redundancy() {
+        let puzzle = classic_from_string(".9...4.2...1.8....6.21.....4....57..1..7.9..2..84....5.....65.8....7.6...5.3...4.".to_string()).unwrap().to_clues();
+        let mut solver = BruteForceSolver::for_empty_grid(&empty_classic());
+        let solution = solver.random_solution(&puzzle).unwrap();
+
+        // With two clues gone, the suggestions should each make the puzzle unique again.
+        let mut clues = puzzle.clone();
+        clues[1] = 0;
+        clues[5] = 0;
+        assert!(!solver.has_unique_solution(&clues));
+        let suggestions = suggest_clue_additions(&empty_classic(), &clues, &SolveConfiguration::with_all_strategies(), Backend::Bitmask, seeded_rng(1));
+        assert!(!suggestions.is_empty());
+        for suggestion in &suggestions {
+            let mut repaired = clues.clone();
+            for &(cell, digit) in &suggestion.clues {
+                repaired[cell] = digit;
+            }
+            assert!(solver.has_unique_solution(&repaired));
+            assert_eq!(suggestion.rating.is_some(), suggestion.steps.is_some());
+        }
+
+        // A clue copied in from the solution is never needed.
+        let redundant = redundant_clues(&empty_classic(), &puzzle, Backend::DancingLinks, seeded_rng(2));
+        let cell = (0..81).find(|&cell| puzzle[cell] == 0).unwrap();
+        let mut extended = puzzle.clone();
+        extended[cell] = solution[cell];
+        let extended_redundant = redundant_clues(&empty_classic(), &extended, Backend::Bitmask, seeded_rng(3));
+        assert!(extended_redundant.contains(&cell));
+        assert!(redundant.iter().all(|cell| extended_redundant.contains(cell)));
+    }
+}
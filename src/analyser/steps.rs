@@ -1,7 +1,7 @@
 //! Analysis that determines how many "steps" are needed to solve a puzzle.
 
 use grid::{Grid, GridSize};
-use strategies::{Deduction, Step, Strategy};
+use strategies::{Deduction, LinkGraph, Step, Strategy};
 
 /// Determine how many "steps" are needed to solve the given puzzle.
 ///
@@ -12,10 +12,11 @@ use strategies::{Deduction, Step, Strategy};
 pub fn steps_to_solve<T: GridSize>(grid: &Grid<T>, strategies: &[Vec<Strategy>]) -> Option<Vec<usize>> {
 
     let mut steps_taken = vec![0; strategies.len()]; let mut working_grid = grid.clone();
+    let link_graph = LinkGraph::new();
     'outer: while !working_grid.is_solved() {
 
         for (idx, strategy_group) in strategies.iter().enumerate() {
-            let moves: Vec<Step<T>> = strategy_group.iter().flat_map(|strat| strat.find_steps(&working_grid)).collect();
+            let moves: Vec<Step<T>> = strategy_group.iter().flat_map(|strat| strat.find_steps_with_links(&working_grid, &link_graph)).collect();
             let deductions: Vec<Deduction> = moves.iter().flat_map(|mov| mov.get_deductions(&working_grid)).collect();
             if !deductions.is_empty() {
                 for deduction in deductions {
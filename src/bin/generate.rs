@@ -1,23 +1,57 @@
 //! A tool to generate Sudoku puzzles
+//!
+//! Pass `--seed <n>` to make the output repeatable. Only a single thread is used in that case, since
+//! the order in which puzzles are found on different threads isn't fixed.
 
 extern crate itertools;
+extern crate rand;
 extern crate rustdoku;
 
 use itertools::Itertools;
+use rand::{FromEntropy, StdRng};
 
-use rustdoku::analyser::steps_to_solve;
+use rustdoku::analyser::{steps_to_solve, Criteria};
 use rustdoku::grid::variants::{empty_classic, classic_from_clues};
 use rustdoku::generator;
+use rustdoku::solver::SolveConfiguration;
 use rustdoku::strategies::Strategy::*;
 
+use std::env;
+use std::process;
+use std::thread;
+
 fn main() {
 
+    let args: Vec<String> = env::args().skip(1).collect();
+    let seed = match args.iter().map(|arg| arg.as_str()).collect::<Vec<_>>().as_slice() {
+        [] => None,
+        ["--seed", seed] => match seed.parse::<u64>() {
+            Ok(seed) => Some(seed),
+            Err(_) => {
+                eprintln!("The seed must be a number");
+                process::exit(2);
+            },
+        },
+        _ => {
+            eprintln!("Usage: generate [--seed <n>]");
+            process::exit(2);
+        },
+    };
+
     let pattern = vec![3, 11, 13, 19, 21, 23, 27, 29, 31, 33, 37, 39, 41, 43, 47, 49, 51, 53, 57, 59, 61, 67, 69, 77];
     let competition_steps = vec![vec![FullHouse, HiddenSingle, NakedSingle], vec![BoxLine], vec![HiddenSubset(2), NakedSubset(2)], vec![HiddenSubset(3), NakedSubset(3)], vec![HiddenSubset(4), NakedSubset(4)], vec![Fish(2)]];
 
+    // Only puzzles solvable with the competition strategies can be counted in steps, so the
+    // workers can filter out all other puzzles before they reach this thread.
+    let criteria = Criteria::solvable_with(SolveConfiguration::with_strategies(competition_steps.concat()));
+    let (rng, num_threads) = match seed {
+        Some(seed) => (generator::seeded_rng(seed), 1),
+        None => (StdRng::from_entropy(), thread::available_parallelism().map_or(1, |threads| threads.get())),
+    };
+
     let empty_grid = empty_classic();
 
-    for clues in generator::generate_puzzles_on_empty_grid_with_pattern(empty_grid, pattern) {
+    for clues in generator::generate_puzzles_on_empty_grid_with_pattern_in_parallel(empty_grid, pattern, criteria, num_threads, rng) {
         let grid = classic_from_clues(&clues).unwrap();
         if let Some(steps) = steps_to_solve(&grid, &competition_steps) {
             println!("{} - {}", steps.iter().rev().join(" "), clues.iter().join(""));
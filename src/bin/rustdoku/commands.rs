@@ -0,0 +1,469 @@
+//! The subcommands of the tool, each of which turns a single puzzle into its output.
+
+use rustdoku::analyser;
+use rustdoku::analyser::{BatchSummary, Criteria, PuzzleReport, Rating};
+use rustdoku::generator;
+use rustdoku::generator::{Canonicalizer, DifficultyTarget, ParallelPuzzlesIterator};
+use rustdoku::grid::{Grid, GridSize};
+use rustdoku::grid::variants::grid_from_empty_grid_and_clues;
+use rustdoku::solver;
+#[cfg(feature = "serde")]
+use rustdoku::solver::HintLevel;
+use rustdoku::strategies::Strategy;
+
+use rand::{thread_rng, FromEntropy, StdRng};
+
+#[cfg(feature = "serde")]
+use formats::{to_json, JsonGrid};
+use formats::{write_grid, Format, ReadError};
+use options::Options;
+
+use std::collections::HashMap;
+use std::io::Write;
+
+/// Solve the puzzle as far as possible, listing the steps taken along the way.
+pub fn solve<T: GridSize>(puzzle: &Grid<T>, options: &Options) -> String {
+
+    let mut grid = puzzle.clone();
+    let details = solver::solve(&mut grid, &options.configuration);
+
+    // Describe each step against the grid as it was when the step was taken.
+    let mut working_grid = puzzle.clone();
+    let mut steps = Vec::new();
+    for (step, deductions) in &details.steps {
+        steps.push((step.get_strategy(), step.get_description(&working_grid), deductions.iter().map(|d| d.get_description(&working_grid)).collect::<Vec<_>>()));
+        for deduction in deductions {
+            working_grid.apply_deduction(deduction.clone());
+        }
+    }
+
+    match options.output_format {
+        #[cfg(feature = "serde")]
+        Format::Json => to_json(&json::Solve {
+            puzzle: puzzle.to_line_string(),
+            result: format!("{:?}", details.result),
+            grid: JsonGrid::from_grid(&grid),
+            steps: if options.quiet { None } else { Some(steps.into_iter().map(|(strategy, description, deductions)| json::SolveStep { strategy: strategy.to_string(), description, deductions }).collect()) },
+        }),
+        format if options.quiet => write_grid(&grid, format),
+        format => {
+            let mut lines = vec![write_grid(puzzle, format)];
+            lines.extend(steps.into_iter().map(|(_, description, deductions)| format!(" - {} - ({})", description, deductions.join(", "))));
+            lines.push(format!("Result: {:?}", details.result));
+            lines.push(write_grid(&grid, format));
+            lines.join("\n") + "\n"
+        },
+    }
+}
+
+/// Rate the puzzle by the hardest strategy needed to solve it.
+pub fn rate<T: GridSize>(puzzle: &Grid<T>, options: &Options) -> String {
+
+    let rating = analyser::rate(puzzle, &options.configuration);
+
+    match options.output_format {
+        #[cfg(feature = "serde")]
+        Format::Json => to_json(&json::Rate {
+            puzzle: puzzle.to_line_string(),
+            solved: rating.is_some(),
+            score: rating.map(|rating| rating.score),
+            hardest_strategy: hardest_strategy_name(rating),
+            steps: rating.map(|rating| rating.num_steps),
+        }),
+        _ if options.quiet => describe_rating(rating),
+        format => format!("{} {}", write_grid(puzzle, format), describe_rating(rating)),
+    }
+}
+
+#[cfg(feature = "serde")]
+fn hardest_strategy_name(rating: Option<Rating>) -> Option<String> {
+    rating.and_then(|rating| rating.hardest_strategy).map(|strategy| strategy.to_string())
+}
+
+fn describe_rating(rating: Option<Rating>) -> String {
+    match rating {
+        Some(Rating { score, hardest_strategy, .. }) => format!("{} {}", score, hardest_strategy.map_or("none".to_string(), |strategy| strategy.to_string())),
+        None => "unsolved".to_string(),
+    }
+}
+
+/// Give a hint for the next step of the puzzle.
+pub fn hint<T: GridSize>(puzzle: &Grid<T>, options: &Options) -> String {
+
+    let hint = solver::hint(puzzle, &options.configuration);
+    let description = match hint {
+        Some(ref hint) => hint.get_description(puzzle, options.hint_level),
+        None if puzzle.is_solved() => "Solved".to_string(),
+        None => "No hint available".to_string(),
+    };
+
+    match options.output_format {
+        #[cfg(feature = "serde")]
+        Format::Json => to_json(&json::Hint {
+            puzzle: puzzle.to_line_string(),
+            hint: description,
+            strategy: hint.as_ref().map(|hint| hint.step.get_strategy().to_string()),
+            deductions: hint.as_ref()
+                .filter(|_| options.hint_level == HintLevel::Full)
+                .map(|hint| hint.deductions.iter().map(|d| d.get_description(puzzle)).collect()),
+        }),
+        _ if options.quiet => description,
+        format => format!("{}\nHint: {}\n", write_grid(puzzle, format), description),
+    }
+}
+
+/// Generate puzzles on the given empty grid, writing each out as soon as it is found. Only puzzles
+/// which can be solved with the configured strategies are kept.
+pub fn generate<T: GridSize + 'static>(empty_grid: &Grid<T>, options: &Options, out: &mut dyn Write) -> Result<(), String> {
+
+    // The workers only pass on puzzles that can be solved, along with their ratings. Puzzles found
+    // on different threads can arrive in any order, so a --seed forces a single worker thread
+    // whatever --threads asks for, keeping the output repeatable.
+    let criteria = Criteria::solvable_with(options.configuration.clone());
+    let (rng, num_threads) = match options.seed {
+        Some(seed) => (generator::seeded_rng(seed), 1),
+        None => (StdRng::from_entropy(), options.threads),
+    };
+
+    // Search around the clue pattern if one is given, and otherwise remove clues symmetrically
+    // from random solutions. A target rating is searched for from the pattern on a single thread.
+    let pattern: Option<Vec<usize>> = options.pattern.as_ref().map(|pattern| pattern.chars().enumerate().filter(|&(_, c)| c != '.' && c != '0').map(|(idx, _)| idx).collect());
+    if pattern.as_ref().is_some_and(|pattern| pattern.iter().any(|&cell| cell >= T::size() * T::size())) {
+        return Err("The pattern is longer than the grid".to_string());
+    }
+    let clues: Box<dyn Iterator<Item = (Vec<usize>, Option<Rating>)>> = match (pattern, options.rating) {
+        (Some(pattern), Some((min, max))) => {
+            let target = DifficultyTarget::RatingBand(options.configuration.clone(), min, max);
+            Box::new(generator::generate_puzzles_on_empty_grid_with_pattern_and_target(empty_grid.clone(), pattern, target, rng).with_backend(options.backend).map(|clues| (clues, None)))
+        },
+        (None, Some(_)) => return Err("A --pattern must be given to search for a --rating".to_string()),
+        (Some(pattern), None) => Box::new(ParallelPuzzlesIterator::for_empty_grid_and_pattern(empty_grid.clone(), pattern, criteria, num_threads, rng).with_backend(options.backend)),
+        (None, None) => Box::new(ParallelPuzzlesIterator::for_empty_grid_with_symmetry(empty_grid.clone(), options.symmetry, options.minimal, criteria, num_threads, rng).with_backend(options.backend)),
+    };
+    // Puzzles from the targeted search are rated here, as they don't come with a rating.
+    let puzzles = clues
+        .filter_map(|(clues, rating)| grid_from_empty_grid_and_clues(empty_grid, &clues).ok().map(|puzzle| (puzzle, rating)))
+        .filter_map(|(puzzle, rating)| rating.or_else(|| analyser::rate(&puzzle, &options.configuration)).map(|rating| (puzzle, rating)))
+        .take(options.count);
+
+    for (puzzle, rating) in puzzles {
+        let output = match options.output_format {
+            #[cfg(feature = "serde")]
+            Format::Json => to_json(&json::Generate {
+                puzzle: puzzle.to_line_string(),
+                score: rating.score,
+                hardest_strategy: hardest_strategy_name(Some(rating)),
+            }),
+            format if options.quiet => write_grid(&puzzle, format),
+            format => format!("{} {}", write_grid(&puzzle, format), describe_rating(Some(rating))),
+        };
+        if writeln!(out, "{}", output).is_err() {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// Summarise the solutions and solve path of the puzzle.
+pub fn analyse<T: GridSize>(puzzle: &Grid<T>, options: &Options) -> String {
+
+    let clues = puzzle.to_clues();
+    let num_clues = clues.iter().filter(|&&clue| clue != 0).count();
+    let mut brute_force = options.backend.for_starting_grid(puzzle, thread_rng());
+    let solutions = if brute_force.has_unique_solution(&clues) { "unique" } else if brute_force.has_any_solution(&clues) { "multiple" } else { "none" };
+
+    let details = solver::solve(&mut puzzle.clone(), &options.configuration);
+    let rating = analyser::rate(puzzle, &options.configuration);
+
+    // Count the steps taken with each strategy, listing the strategies in configuration order.
+    let mut counts: HashMap<Strategy, usize> = HashMap::new();
+    for (step, _) in &details.steps {
+        *counts.entry(step.get_strategy()).or_insert(0) += 1;
+    }
+    let strategy_counts: Vec<(Strategy, usize)> = options.configuration.strategies().iter()
+        .filter_map(|strategy| counts.get(strategy).map(|&count| (*strategy, count)))
+        .collect();
+
+    match options.output_format {
+        #[cfg(feature = "serde")]
+        Format::Json => to_json(&json::Analyse {
+            puzzle: puzzle.to_line_string(),
+            clues: num_clues,
+            solutions,
+            result: format!("{:?}", details.result),
+            steps: details.steps.len(),
+            score: rating.map(|rating| rating.score),
+            strategies: strategy_counts.iter().map(|&(strategy, count)| json::StrategyCount { strategy: strategy.to_string(), count }).collect(),
+        }),
+        _ if options.quiet => format!("{} {} {:?} {} {}", num_clues, solutions, details.result, details.steps.len(), describe_rating(rating)),
+        format => {
+            let mut lines = vec![
+                write_grid(puzzle, format),
+                format!("Clues: {}", num_clues),
+                format!("Solutions: {}", solutions),
+                format!("Result: {:?}", details.result),
+                format!("Steps: {}", details.steps.len()),
+                format!("Rating: {}", describe_rating(rating)),
+            ];
+            lines.extend(strategy_counts.iter().map(|(strategy, count)| format!(" - {}: {}", strategy, count)));
+            lines.join("\n") + "\n"
+        },
+    }
+}
+
+/// Transform the puzzle into its canonical form under the symmetries of its variant.
+pub fn canonicalize<T: GridSize>(puzzle: &Grid<T>, empty_grid: &Grid<T>, canonicalizer: &Canonicalizer<T>, options: &Options) -> String {
+
+    let (canonical, _) = canonicalizer.canonicalize(&puzzle.to_clues());
+    let canonical = match grid_from_empty_grid_and_clues(empty_grid, &canonical) {
+        Ok(grid) => grid,
+        Err(err) => return err.to_string(),
+    };
+
+    match options.output_format {
+        #[cfg(feature = "serde")]
+        Format::Json => to_json(&json::Canonicalize { puzzle: puzzle.to_line_string(), canonical: canonical.to_line_string() }),
+        format if options.quiet => write_grid(&canonical, format),
+        format => format!("{} {}", write_grid(puzzle, format), write_grid(&canonical, format)),
+    }
+}
+
+/// Count the solutions to the puzzle by brute force.
+pub fn count_solutions<T: GridSize>(puzzle: &Grid<T>, options: &Options) -> String {
+
+    let count = options.backend.for_starting_grid(puzzle, thread_rng()).count_solutions(&puzzle.to_clues());
+
+    match options.output_format {
+        #[cfg(feature = "serde")]
+        Format::Json => to_json(&json::CountSolutions { puzzle: puzzle.to_line_string(), solutions: count }),
+        _ if options.quiet => count.to_string(),
+        format => format!("{} {}", write_grid(puzzle, format), count),
+    }
+}
+
+/// Solve and rate the puzzles in parallel as they arrive, writing out a line for each puzzle in
+/// input order, followed by statistics about the whole batch unless in quiet mode.
+pub fn batch<T: GridSize, I>(puzzles: I, options: &Options, out: &mut dyn Write) -> Result<(), String>
+    where I: Iterator<Item = Result<Grid<T>, ReadError>> + Send {
+
+    let (mut summary, mut index, mut invalid) = (BatchSummary::default(), 0, 0);
+    let (mut input_error, mut closed) = (None, false);
+
+    analyser::solve_stream(puzzles, &options.configuration, options.threads, |report| {
+        index += 1;
+        let output = match report {
+            Ok(report) => {
+                summary.add_report(&report, &options.configuration);
+                describe_report(index, &report, options)
+            },
+            Err(ReadError::Puzzle(err)) => {
+                invalid += 1;
+                match options.output_format {
+                    #[cfg(feature = "serde")]
+                    Format::Json => to_json(&json::Invalid { index, status: "Invalid", error: err }),
+                    _ => format!("{} Invalid - {}", index, err),
+                }
+            },
+            Err(ReadError::Input(err)) => {
+                input_error = Some(format!("Couldn't read the input: {}", err));
+                return false;
+            },
+        };
+        closed = writeln!(out, "{}", output).is_err();
+        !closed
+    });
+
+    if let Some(err) = input_error {
+        return Err(err);
+    }
+    if !options.quiet && !closed {
+        let _ = writeln!(out, "{}", describe_summary(&summary, invalid, options.output_format));
+    }
+
+    Ok(())
+}
+
+fn describe_report<T: GridSize>(index: usize, report: &PuzzleReport<T>, options: &Options) -> String {
+    let millis = report.duration.as_secs_f64() * 1000.0;
+    match options.output_format {
+        #[cfg(feature = "serde")]
+        Format::Json => to_json(&json::Report {
+            index,
+            status: format!("{:?}", report.details.result),
+            puzzle: report.puzzle.to_line_string(),
+            grid: JsonGrid::from_grid(&report.grid),
+            score: report.rating.map(|rating| rating.score),
+            hardest_strategy: hardest_strategy_name(report.rating),
+            steps: report.details.steps.len(),
+            time_ms: millis,
+        }),
+        format => format!("{} {:?} {} {} {:.3}ms {}", index, report.details.result, report.details.steps.len(), describe_rating(report.rating), millis, write_grid(&report.puzzle, format)),
+    }
+}
+
+fn describe_summary(summary: &BatchSummary, invalid: usize, format: Format) -> String {
+    let total_millis = summary.total_time.as_secs_f64() * 1000.0;
+    let mean_millis = if summary.num_puzzles > 0 { total_millis / summary.num_puzzles as f64 } else { 0.0 };
+    match format {
+        #[cfg(feature = "serde")]
+        Format::Json => to_json(&json::Summary { summary: json::BatchSummary {
+            puzzles: summary.num_puzzles + invalid,
+            solved: summary.solved,
+            contradiction: summary.contradiction,
+            insufficient_strategies: summary.insufficient_strategies,
+            invalid,
+            total_time_ms: total_millis,
+            mean_time_ms: mean_millis,
+            max_time_ms: summary.max_time.as_secs_f64() * 1000.0,
+            strategy_usage: summary.strategy_usage.iter().map(|&(strategy, puzzles, steps)| json::StrategyUsage { strategy: strategy.to_string(), puzzles, steps }).collect(),
+            hardest_strategies: summary.hardest_strategies.iter().map(|&(strategy, puzzles)| json::HardestStrategy { strategy: strategy.to_string(), puzzles }).collect(),
+        }}),
+        _ => {
+            let mut lines = vec![
+                String::new(),
+                format!("Puzzles: {}", summary.num_puzzles + invalid),
+                format!(" - Solved: {}", summary.solved),
+                format!(" - Contradiction: {}", summary.contradiction),
+                format!(" - InsufficientStrategies: {}", summary.insufficient_strategies),
+                format!(" - Invalid: {}", invalid),
+                format!("Time: {:.3}ms total, {:.3}ms mean, {:.3}ms max", total_millis, mean_millis, summary.max_time.as_secs_f64() * 1000.0),
+                "Strategy usage (puzzles, steps):".to_string(),
+            ];
+            lines.extend(summary.strategy_usage.iter().map(|(strategy, puzzles, steps)| format!(" - {}: {}, {}", strategy, puzzles, steps)));
+            lines.push("Hardest strategy needed (puzzles):".to_string());
+            lines.extend(summary.hardest_strategies.iter().map(|(strategy, puzzles)| format!(" - {}: {}", strategy, puzzles)));
+            lines.join("\n")
+        },
+    }
+}
+
+/// The objects written out by each command in JSON format.
+#[cfg(feature = "serde")]
+mod json {
+
+    use formats::JsonGrid;
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    pub struct Solve {
+        pub puzzle: String,
+        pub result: String,
+        pub grid: JsonGrid,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub steps: Option<Vec<SolveStep>>,
+    }
+
+    #[derive(Serialize)]
+    pub struct SolveStep {
+        pub strategy: String,
+        pub description: String,
+        pub deductions: Vec<String>,
+    }
+
+    #[derive(Serialize)]
+    pub struct Rate {
+        pub puzzle: String,
+        pub solved: bool,
+        pub score: Option<usize>,
+        pub hardest_strategy: Option<String>,
+        pub steps: Option<usize>,
+    }
+
+    #[derive(Serialize)]
+    pub struct Hint {
+        pub puzzle: String,
+        pub hint: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub strategy: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub deductions: Option<Vec<String>>,
+    }
+
+    #[derive(Serialize)]
+    pub struct Generate {
+        pub puzzle: String,
+        pub score: usize,
+        pub hardest_strategy: Option<String>,
+    }
+
+    #[derive(Serialize)]
+    pub struct Analyse {
+        pub puzzle: String,
+        pub clues: usize,
+        pub solutions: &'static str,
+        pub result: String,
+        pub steps: usize,
+        pub score: Option<usize>,
+        pub strategies: Vec<StrategyCount>,
+    }
+
+    #[derive(Serialize)]
+    pub struct StrategyCount {
+        pub strategy: String,
+        pub count: usize,
+    }
+
+    #[derive(Serialize)]
+    pub struct Canonicalize {
+        pub puzzle: String,
+        pub canonical: String,
+    }
+
+    #[derive(Serialize)]
+    pub struct CountSolutions {
+        pub puzzle: String,
+        pub solutions: usize,
+    }
+
+    #[derive(Serialize)]
+    pub struct Report {
+        pub index: usize,
+        pub status: String,
+        pub puzzle: String,
+        pub grid: JsonGrid,
+        pub score: Option<usize>,
+        pub hardest_strategy: Option<String>,
+        pub steps: usize,
+        pub time_ms: f64,
+    }
+
+    #[derive(Serialize)]
+    pub struct Invalid {
+        pub index: usize,
+        pub status: &'static str,
+        pub error: String,
+    }
+
+    #[derive(Serialize)]
+    pub struct Summary {
+        pub summary: BatchSummary,
+    }
+
+    #[derive(Serialize)]
+    pub struct BatchSummary {
+        pub puzzles: usize,
+        pub solved: usize,
+        pub contradiction: usize,
+        pub insufficient_strategies: usize,
+        pub invalid: usize,
+        pub total_time_ms: f64,
+        pub mean_time_ms: f64,
+        pub max_time_ms: f64,
+        pub strategy_usage: Vec<StrategyUsage>,
+        pub hardest_strategies: Vec<HardestStrategy>,
+    }
+
+    #[derive(Serialize)]
+    pub struct StrategyUsage {
+        pub strategy: String,
+        pub puzzles: usize,
+        pub steps: usize,
+    }
+
+    #[derive(Serialize)]
+    pub struct HardestStrategy {
+        pub strategy: String,
+        pub puzzles: usize,
+    }
+}
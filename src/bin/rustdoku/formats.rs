@@ -0,0 +1,186 @@
+//! Reading and writing puzzles in the formats supported on the command line.
+
+use rustdoku::grid::{Grid, GridSize};
+use rustdoku::grid::pencilmarks::from_pencilmarks;
+use rustdoku::grid::variants::grid_from_empty_grid_and_string;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use std::io;
+use std::io::BufRead;
+use std::iter;
+use std::str::FromStr;
+
+/// The ways in which a grid can be written down.
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum Format {
+    /// The values of the grid on a single line, with '.' or '0' for empty cells.
+    Line,
+    /// The candidates of every cell, laid out as a grid over several lines.
+    Pencilmark,
+    /// One JSON object per line.
+    #[cfg(feature = "serde")]
+    Json,
+}
+
+impl FromStr for Format {
+
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Format, String> {
+        match input {
+            "line" => Ok(Format::Line),
+            "pencilmark" => Ok(Format::Pencilmark),
+            #[cfg(feature = "serde")]
+            "json" => Ok(Format::Json),
+            #[cfg(not(feature = "serde"))]
+            "json" => Err("The json format requires rustdoku to be built with the serde feature".to_string()),
+            _ => Err(format!("Unknown format '{}'", input)),
+        }
+    }
+}
+
+/// An error met while reading puzzles.
+pub enum ReadError {
+    /// The input itself couldn't be read.
+    Input(io::Error),
+    /// A single puzzle couldn't be understood, though others might still be.
+    Puzzle(String),
+}
+
+impl From<io::Error> for ReadError {
+    fn from(err: io::Error) -> ReadError {
+        ReadError::Input(err)
+    }
+}
+
+/// Read the puzzles from the given input, one at a time as the lines holding each arrive. Blank
+/// lines, and lines starting with `//` or `#`, are ignored.
+pub fn read_puzzles<'a, T: GridSize, R: BufRead + 'a>(empty_grid: &'a Grid<T>, format: Format, input: R) -> Box<dyn Iterator<Item = Result<Grid<T>, ReadError>> + 'a> {
+
+    let mut lines = input.lines()
+        .filter(|line| line.as_ref().map_or(true, |line| !line.trim().is_empty() && !line.starts_with("//") && !line.starts_with('#')));
+
+    match format {
+        Format::Line => Box::new(lines.map(move |line| read_line(empty_grid, &line?).map_err(ReadError::Puzzle))),
+        #[cfg(feature = "serde")]
+        Format::Json => Box::new(lines.map(move |line| read_json(empty_grid, &line?).map_err(ReadError::Puzzle))),
+        Format::Pencilmark => Box::new(iter::from_fn(move || {
+            // A pencilmark grid spans several lines, so gather lines until there is an entry for
+            // every cell.
+            let (mut current, mut entries) = (String::new(), 0);
+            while entries < T::size() * T::size() {
+                let line = match lines.next() {
+                    Some(Ok(line)) => line,
+                    Some(Err(err)) => return Some(Err(ReadError::Input(err))),
+                    None if entries > 0 => return Some(Err(ReadError::Puzzle("Incomplete pencilmark grid at end of input".to_string()))),
+                    None => return None,
+                };
+                entries += line.split(|c: char| !c.is_ascii_digit()).filter(|s| !s.is_empty()).count();
+                current.push_str(&line);
+                current.push('\n');
+            }
+            Some(from_pencilmarks(empty_grid, &current).map_err(|err| ReadError::Puzzle(err.to_string())))
+        })),
+    }
+}
+
+fn read_line<T: GridSize>(empty_grid: &Grid<T>, line: &str) -> Result<Grid<T>, String> {
+    grid_from_empty_grid_and_string(empty_grid, line.trim().to_string()).map_err(|err| err.to_string())
+}
+
+/// A grid as written out in JSON - its values on a single line, along with the candidates of each
+/// cell. The candidates can be left out of grids which are read in, and the values can be given
+/// as `puzzle` instead.
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+pub struct JsonGrid {
+    #[serde(alias = "puzzle")]
+    values: String,
+    #[serde(default)]
+    candidates: Option<Vec<Vec<usize>>>,
+}
+
+#[cfg(feature = "serde")]
+impl JsonGrid {
+    pub fn from_grid<T: GridSize>(grid: &Grid<T>) -> JsonGrid {
+        JsonGrid { values: grid.to_line_string(), candidates: Some(grid.cells().map(|cell| grid.candidates(cell).iter().collect())) }
+    }
+}
+
+/// Read a puzzle given as a JSON string holding the puzzle on a single line, or as a `JsonGrid`.
+#[cfg(feature = "serde")]
+fn read_json<T: GridSize>(empty_grid: &Grid<T>, line: &str) -> Result<Grid<T>, String> {
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum JsonPuzzle {
+        Line(String),
+        Grid(JsonGrid),
+    }
+
+    let (values, candidates) = match serde_json::from_str(line).map_err(|err| err.to_string())? {
+        JsonPuzzle::Line(puzzle) => return read_line(empty_grid, &puzzle),
+        JsonPuzzle::Grid(JsonGrid { values, candidates: None }) => return read_line(empty_grid, &values),
+        JsonPuzzle::Grid(JsonGrid { values, candidates: Some(candidates) }) => (values, candidates),
+    };
+
+    // Place the values, and then remove whatever isn't listed from the candidates of each cell
+    // which is still empty. A cell with a single candidate stays empty until it is placed.
+    let mut grid = read_line(empty_grid, &values)?;
+    if candidates.len() != T::size() * T::size() {
+        return Err("The values and candidates have different lengths".to_string());
+    }
+    for (cell, cell_candidates) in candidates.iter().enumerate() {
+        if grid.is_empty(cell) {
+            for value in grid.values() {
+                if !cell_candidates.contains(&value) {
+                    grid.eliminate_value(cell, value);
+                }
+            }
+            if grid.num_candidates(cell) == 0 {
+                return Err(format!("The empty cell at position {} has no candidates", cell));
+            }
+        }
+    }
+
+    Ok(grid)
+}
+
+/// Write out a value as JSON on a single line.
+#[cfg(feature = "serde")]
+pub fn to_json<S: Serialize>(value: &S) -> String {
+    serde_json::to_string(value).unwrap()
+}
+
+/// Write out a grid in the given format. JSON grids are written as a `JsonGrid`.
+pub fn write_grid<T: GridSize>(grid: &Grid<T>, format: Format) -> String {
+    match format {
+        Format::Line => grid.to_line_string(),
+        Format::Pencilmark => grid.to_pencilmark_string(),
+        #[cfg(feature = "serde")]
+        Format::Json => to_json(&JsonGrid::from_grid(grid)),
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+
+    use rustdoku::grid::variants::{classic_from_string, empty_classic};
+    use super::*;
+
+    #[test]
+    fn test_json_round_trip() {
+        let mut grid = classic_from_string(".287...5..54..398.........7..1.9......63......9...43......5....5.2.....66..17...9".to_string()).unwrap();
+        for value in 2..10 {
+            grid.eliminate_value(0, value);
+        }
+
+        let json = write_grid(&grid, Format::Json);
+        let parsed = read_json(&empty_classic(), &json).unwrap();
+        assert!(parsed.is_empty(0));
+        assert_eq!(parsed.to_pencilmark_string(), grid.to_pencilmark_string());
+        assert_eq!(write_grid(&parsed, Format::Json), json);
+    }
+}
@@ -0,0 +1,129 @@
+//! A command-line tool which solves, rates, analyses and generates Sudoku puzzles.
+//!
+//! Puzzles are read from stdin, and the results for each puzzle are written to stdout in the same
+//! order, so that the tool can be used in shell pipelines.
+
+extern crate rand;
+extern crate rustdoku;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(feature = "serde")]
+extern crate serde_json;
+
+mod commands;
+mod formats;
+mod options;
+
+use rustdoku::generator::Canonicalizer;
+use rustdoku::grid::{Grid, GridSize};
+use rustdoku::grid::variants::*;
+
+use formats::ReadError;
+use options::{Command, Options};
+
+use std::env;
+use std::io;
+use std::io::Write;
+use std::process;
+use std::sync::mpsc;
+use std::thread;
+
+fn main() {
+
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.is_empty() || args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        print!("{}", options::USAGE);
+        return;
+    }
+
+    let options = match options::parse_options(&args) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, options::USAGE);
+            process::exit(2);
+        },
+    };
+
+    let result = match options.variant.as_str() {
+        "classic" => run(&empty_classic(), &options),
+        "six-by-six" => run(&empty_six_by_six(), &options),
+        "diagonal" => run(&empty_diagonal(), &options),
+        "antidiagonal" => run(&empty_antidiagonal(), &options),
+        "bent-diagonal" => run(&empty_bent_diagonal(), &options),
+        "windoku" => run(&empty_windoku(), &options),
+        "antiwindoku" => run(&empty_antiwindoku(), &options),
+        "antiknight" => run(&empty_antiknight(), &options),
+        "untouch" => run(&empty_untouch(), &options),
+        "disjoint-groups" => run(&empty_disjoint_groups(), &options),
+        other => Err(format!("Unknown variant '{}'", other)),
+    };
+
+    if let Err(err) = result {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
+}
+
+/// Run the chosen command over every puzzle on stdin. Puzzles which can't be read are reported on
+/// stderr, and cause the tool to fail once all of the other puzzles have been dealt with.
+fn run<T: GridSize + 'static>(empty_grid: &Grid<T>, options: &Options) -> Result<(), String> {
+
+    // Stop quietly if the output is closed early, as it is when piped into `head`.
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+
+    if options.command == Command::Generate {
+        return commands::generate(empty_grid, options, &mut out);
+    }
+
+    if options.command == Command::Batch {
+        // The puzzles are read on a thread of their own, so that the result for each puzzle can
+        // be written out as soon as it's ready without waiting on the next line of input.
+        let (sender, receiver) = mpsc::sync_channel(options.threads);
+        let (empty_grid, input_format) = (empty_grid.clone(), options.input_format);
+        thread::spawn(move || {
+            let stdin = io::stdin();
+            for puzzle in formats::read_puzzles(&empty_grid, input_format, stdin.lock()) {
+                if sender.send(puzzle).is_err() {
+                    break;
+                }
+            }
+        });
+        return commands::batch(receiver.into_iter(), options, &mut out);
+    }
+
+    // Finding the symmetries of the variant can take a while, so only do it once.
+    let canonicalizer = if options.command == Command::Canonicalize {
+        Some(Canonicalizer::for_grid(empty_grid).map_err(|err| err.to_string())?)
+    } else {
+        None
+    };
+
+    let stdin = io::stdin();
+    let mut failures = 0;
+    for (idx, puzzle) in formats::read_puzzles(empty_grid, options.input_format, stdin.lock()).enumerate() {
+        match puzzle {
+            Ok(puzzle) => {
+                let output = match options.command {
+                    Command::Solve => commands::solve(&puzzle, options),
+                    Command::Rate => commands::rate(&puzzle, options),
+                    Command::Hint => commands::hint(&puzzle, options),
+                    Command::Analyse => commands::analyse(&puzzle, options),
+                    Command::Canonicalize => commands::canonicalize(&puzzle, empty_grid, canonicalizer.as_ref().unwrap(), options),
+                    Command::CountSolutions => commands::count_solutions(&puzzle, options),
+                    Command::Generate | Command::Batch => unreachable!(),
+                };
+                if writeln!(out, "{}", output).is_err() {
+                    return Ok(());
+                }
+            },
+            Err(ReadError::Puzzle(err)) => {
+                eprintln!("Puzzle {}: {}", idx + 1, err);
+                failures += 1;
+            },
+            Err(ReadError::Input(err)) => return Err(format!("Couldn't read the input: {}", err)),
+        }
+    }
+
+    if failures > 0 { Err(format!("{} puzzles could not be read", failures)) } else { Ok(()) }
+}
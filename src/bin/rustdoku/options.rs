@@ -0,0 +1,189 @@
+//! Parsing of the command-line arguments.
+
+use rustdoku::generator::{Backend, Symmetry};
+use rustdoku::solver::{HintLevel, SolveConfiguration};
+use rustdoku::strategies::Strategy;
+
+use formats::Format;
+
+use std::thread;
+
+pub const USAGE: &str = "\
+Usage: rustdoku <command> [options]
+
+Commands:
+    solve              Solve each puzzle as far as possible, listing the steps taken
+    rate               Rate each puzzle by the hardest strategy needed to solve it
+    hint               Give a hint for the next step of each puzzle
+    generate           Generate new puzzles with a unique solution
+    analyse            Give a summary of the solve path and solutions of each puzzle
+    canonicalize       Transform each puzzle into its minlex form under the symmetries of
+                       its variant
+    count-solutions    Count the solutions of each puzzle using brute force
+    batch              Solve and rate the puzzles in parallel, reporting the result and timing
+                       of each puzzle followed by summary statistics
+
+Options:
+    --variant <name>           The variant of the puzzles (default: classic). One of classic,
+                               six-by-six, diagonal, antidiagonal, bent-diagonal, windoku,
+                               antiwindoku, antiknight, untouch or disjoint-groups
+    --strategies <list>        A comma-separated list of the strategies to use, in order, such as
+                               full-house,hidden-single,naked-single (default: all)
+    --input-format <format>    The format of the puzzles read from stdin: line, pencilmark or json
+                               (default: line)
+    --output-format <format>   The format of the grids written out: line, pencilmark or json
+                               (default: line)
+    --level <level>            How much a hint reveals: strategy, location or full (default: full)
+    --count <n>                The number of puzzles to generate (default: 1)
+    --pattern <clues>          The positions of the clues for generated puzzles, given as a line
+                               with '.' for the empty cells. Without a pattern, clues are removed
+                               from random solutions instead
+    --symmetry <symmetry>      The symmetry of the clues of generated puzzles when no pattern is
+                               given: none, rotational, mirror or diagonal (default: rotational)
+    --minimal                  Only generate puzzles from which no clue can be removed
+    --rating <min>[-<max>]     Search for puzzles whose rating lies in the given range, where a
+                               rating is the position of the hardest strategy needed within
+                               --strategies. Requires a --pattern, and uses a single thread
+    --threads <n>              The number of threads used by batch and generate (default: one
+                               per CPU). Generate ignores this when given a --seed
+    --backend <backend>        The brute-force solver used by generate, analyse and count-solutions:
+                               bitmask or dancing-links (default: bitmask)
+    --seed <n>                 Seed the random choices of generate so that its output can be
+                               repeated. This forces generate onto a single worker thread
+    -q, --quiet                Write only the result for each puzzle, one per line
+    -h, --help                 Show this message
+";
+
+/// The subcommands of the tool.
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum Command {
+    Solve,
+    Rate,
+    Hint,
+    Generate,
+    Analyse,
+    Canonicalize,
+    CountSolutions,
+    Batch,
+}
+
+/// All of the options given on the command line.
+pub struct Options {
+    pub command: Command,
+    pub variant: String,
+    pub configuration: SolveConfiguration,
+    pub input_format: Format,
+    pub output_format: Format,
+    pub hint_level: HintLevel,
+    pub count: usize,
+    pub pattern: Option<String>,
+    pub symmetry: Symmetry,
+    pub minimal: bool,
+    pub rating: Option<(usize, usize)>,
+    pub threads: usize,
+    pub backend: Backend,
+    pub seed: Option<u64>,
+    pub quiet: bool,
+}
+
+/// Read the options from the given command-line arguments, excluding the program name.
+pub fn parse_options(args: &[String]) -> Result<Options, String> {
+
+    let command = match args.first().map(|arg| arg.as_str()) {
+        Some("solve") => Command::Solve,
+        Some("rate") => Command::Rate,
+        Some("hint") => Command::Hint,
+        Some("generate") => Command::Generate,
+        Some("analyse") | Some("analyze") => Command::Analyse,
+        Some("canonicalize") | Some("canonicalise") => Command::Canonicalize,
+        Some("count-solutions") => Command::CountSolutions,
+        Some("batch") => Command::Batch,
+        Some(other) => return Err(format!("Unknown command '{}'", other)),
+        None => return Err("No command given".to_string()),
+    };
+
+    let mut options = Options {
+        command,
+        variant: "classic".to_string(),
+        configuration: SolveConfiguration::with_all_strategies(),
+        input_format: Format::Line,
+        output_format: Format::Line,
+        hint_level: HintLevel::Full,
+        count: 1,
+        pattern: None,
+        symmetry: Symmetry::Rotational,
+        minimal: false,
+        rating: None,
+        threads: thread::available_parallelism().map_or(1, |threads| threads.get()),
+        backend: Backend::Bitmask,
+        seed: None,
+        quiet: false,
+    };
+
+    let mut remaining = args[1..].iter();
+    while let Some(arg) = remaining.next() {
+        let mut value = || remaining.next().ok_or_else(|| format!("Missing value for {}", arg));
+        match arg.as_str() {
+            "--variant" => options.variant = value()?.clone(),
+            "--strategies" => options.configuration = parse_strategies(value()?)?,
+            "--input-format" => options.input_format = value()?.parse()?,
+            "--output-format" => options.output_format = value()?.parse()?,
+            "--level" => options.hint_level = parse_hint_level(value()?)?,
+            "--count" => options.count = value()?.parse().map_err(|_| "The count must be a number".to_string())?,
+            "--pattern" => options.pattern = Some(value()?.clone()),
+            "--symmetry" => options.symmetry = parse_symmetry(value()?)?,
+            "--minimal" => options.minimal = true,
+            "--rating" => options.rating = Some(parse_rating_band(value()?)?),
+            "--threads" => options.threads = value()?.parse().map_err(|_| "The number of threads must be a number".to_string())?,
+            "--backend" => options.backend = parse_backend(value()?)?,
+            "--seed" => options.seed = Some(value()?.parse().map_err(|_| "The seed must be a number".to_string())?),
+            "-q" | "--quiet" => options.quiet = true,
+            other => return Err(format!("Unknown option '{}'", other)),
+        }
+    }
+
+    Ok(options)
+}
+
+fn parse_strategies(input: &str) -> Result<SolveConfiguration, String> {
+    if input == "all" {
+        return Ok(SolveConfiguration::with_all_strategies());
+    }
+    let strategies = input.split(',').map(|name| name.trim().parse::<Strategy>()).collect::<Result<Vec<_>, _>>()?;
+    Ok(SolveConfiguration::with_strategies(strategies))
+}
+
+fn parse_hint_level(input: &str) -> Result<HintLevel, String> {
+    match input {
+        "strategy" => Ok(HintLevel::Strategy),
+        "location" => Ok(HintLevel::Location),
+        "full" => Ok(HintLevel::Full),
+        _ => Err(format!("Unknown hint level '{}'", input)),
+    }
+}
+
+fn parse_symmetry(input: &str) -> Result<Symmetry, String> {
+    match input {
+        "none" => Ok(Symmetry::None),
+        "rotational" => Ok(Symmetry::Rotational),
+        "mirror" => Ok(Symmetry::Mirror),
+        "diagonal" => Ok(Symmetry::Diagonal),
+        _ => Err(format!("Unknown symmetry '{}'", input)),
+    }
+}
+
+fn parse_backend(input: &str) -> Result<Backend, String> {
+    match input {
+        "bitmask" => Ok(Backend::Bitmask),
+        "dancing-links" => Ok(Backend::DancingLinks),
+        _ => Err(format!("Unknown backend '{}'", input)),
+    }
+}
+
+fn parse_rating_band(input: &str) -> Result<(usize, usize), String> {
+    let parse = |bound: &str| bound.trim().parse::<usize>().map_err(|_| format!("Invalid rating range '{}'", input));
+    match input.find('-') {
+        Some(idx) => Ok((parse(&input[..idx])?, parse(&input[idx + 1..])?)),
+        None => parse(input).map(|rating| (rating, rating)),
+    }
+}
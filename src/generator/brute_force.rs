@@ -2,11 +2,25 @@
 
 use rand::prelude::*;
 
+use generator::dancing_links::DancingLinksSolver;
 use grid::{Grid, GridSize};
+use grid::cellset::CellSet;
 
 type Cell = usize;
 type House = usize;
-type DigitMask = usize;
+
+/// The digits that a cell could hold, where bit `d - 1` stands for the digit `d`. Grids with up to
+/// 25 digits fit comfortably.
+pub type DigitMask = usize;
+
+/// An extra rule which the brute-force solver enforces alongside the regions and neighbours of the
+/// grid, such as a killer cage or a thermometer.
+pub trait Constraint: Send + Sync {
+    /// Remove any digits that the rule forbids from the given masks, one for each cell of the
+    /// grid, in which a placed cell holds just its own digit. Only digits may be removed, never
+    /// added. Returns false if the rule can no longer be met.
+    fn propagate(&self, masks: &mut [DigitMask]) -> bool;
+}
 
 struct ConstantData {
     num_digits: usize,
@@ -16,8 +30,6 @@ struct ConstantData {
     cells_for_house: Vec<Vec<Cell>>,
     houses_for_cell: Vec<Vec<House>>,
     mask_for_digit: Vec<DigitMask>,
-    digits_in_mask: Vec<usize>,
-    possible_guesses_for_mask: Vec<Vec<DigitMask>>,
     neighbours_for_cell: Vec<Vec<Cell>>,
     start_state: BoardState,
 }
@@ -37,7 +49,7 @@ impl BoardState {
         BoardState {
             cells: vec![(1 << num_digits) - 1; num_cells],
             cells_remaining: num_cells,
-            solved_in_house: vec![0; grid.all_regions().len()],
+            solved_in_house: vec![0; BruteForceSolver::get_num_houses_from_grid(grid)],
             solution: vec![0; num_cells],
         }
     }
@@ -47,7 +59,7 @@ impl BoardState {
         BoardState {
             cells: (0..num_cells).map(|cell| Self::maskify(grid, cell)).collect(),
             cells_remaining: num_cells,
-            solved_in_house: vec![0; grid.all_regions().len()],
+            solved_in_house: vec![0; BruteForceSolver::get_num_houses_from_grid(grid)],
             solution: vec![0; num_cells],
         }
     }
@@ -64,6 +76,89 @@ impl BoardState {
     }
 }
 
+/// A solver which finds the solutions of puzzles by exhaustive search, so that the generator and
+/// analyser can work with whichever backend suits them.
+pub trait BruteForce: Send {
+
+    /// Iterate over the solutions of the puzzle with the given clues, stopping after `limit` of
+    /// them.
+    fn solutions<'a>(&'a mut self, clues: &[usize], limit: usize) -> Box<dyn Iterator<Item = Vec<usize>> + 'a>;
+
+    /// Find a solution of the puzzle with the given clues, chosen at random, if there are any.
+    fn random_solution(&mut self, clues: &[usize]) -> Option<Vec<usize>>;
+
+    /// Check whether the puzzle with the given clues has exactly one solution.
+    fn has_unique_solution(&mut self, clues: &[usize]) -> bool {
+        self.solutions(clues, 2).count() == 1
+    }
+
+    /// Check whether the puzzle with the given clues can be solved at all.
+    fn has_any_solution(&mut self, clues: &[usize]) -> bool {
+        self.solutions(clues, 1).count() > 0
+    }
+
+    /// Count every solution of the puzzle with the given clues.
+    fn count_solutions(&mut self, clues: &[usize]) -> usize {
+        self.solutions(clues, usize::MAX).count()
+    }
+
+    /// For each cell, the digits that it takes across all solutions of the puzzle with the given
+    /// clues, in increasing order. A cell with a single digit is forced, while a cell with several
+    /// can be pinned down by adding it as a clue. Every cell is empty if there are no solutions.
+    ///
+    /// Rather than listing every solution, each digit not yet seen in a cell is tried as an extra
+    /// clue, and any solution found adds all of its digits at once.
+    fn backbone(&mut self, clues: &[usize]) -> Vec<Vec<usize>> {
+        let size = (1..).find(|size| size * size >= clues.len()).unwrap();
+        let mut digits = vec![vec![false; size + 1]; clues.len()];
+        if let Some(solution) = self.random_solution(clues) {
+            for (cell, &digit) in solution.iter().enumerate() { digits[cell][digit] = true; }
+            let mut trial = clues.to_vec();
+            for cell in (0..clues.len()).filter(|&cell| clues[cell] == 0) {
+                for digit in 1..size + 1 {
+                    if digits[cell][digit] { continue; }
+                    trial[cell] = digit;
+                    if let Some(solution) = self.random_solution(&trial) {
+                        for (cell, &digit) in solution.iter().enumerate() { digits[cell][digit] = true; }
+                    }
+                }
+                trial[cell] = 0;
+            }
+        }
+        digits.iter().map(|seen| (1..size + 1).filter(|&digit| seen[digit]).collect()).collect()
+    }
+}
+
+/// The brute-force solvers to choose between.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Backend {
+    /// `BruteForceSolver`, which keeps the digits of each cell as a bitmask. It is the faster of
+    /// the two, and the only one which can enforce extra constraints.
+    Bitmask,
+    /// `DancingLinksSolver`, which solves an exact cover problem built from the regions and
+    /// neighbours of the grid.
+    DancingLinks,
+}
+
+impl Backend {
+
+    /// A solver using this backend for puzzles on the given empty grid.
+    pub fn for_empty_grid<T: GridSize, R: Rng>(self, grid: &Grid<T>, rng: R) -> Box<dyn BruteForce> {
+        match self {
+            Backend::Bitmask => Box::new(BruteForceSolver::for_empty_grid(grid).with_rng(rng)),
+            Backend::DancingLinks => Box::new(DancingLinksSolver::for_empty_grid(grid).with_rng(rng)),
+        }
+    }
+
+    /// A solver using this backend for puzzles with the given starting grid.
+    pub fn for_starting_grid<T: GridSize, R: Rng>(self, grid: &Grid<T>, rng: R) -> Box<dyn BruteForce> {
+        match self {
+            Backend::Bitmask => Box::new(BruteForceSolver::for_starting_grid(grid).with_rng(rng)),
+            Backend::DancingLinks => Box::new(DancingLinksSolver::for_starting_grid(grid).with_rng(rng)),
+        }
+    }
+}
+
 #[derive(Copy, Clone)]
 struct Placement {
     cell: Cell,
@@ -90,6 +185,10 @@ pub struct BruteForceSolver {
 
     placement_queue: Vec<Placement>,
     guess_stack: Vec<Guess>,
+
+    constraints: Vec<Box<dyn Constraint>>,
+
+    rng: StdRng,
 }
 
 impl BruteForceSolver {
@@ -104,8 +203,6 @@ impl BruteForceSolver {
             cells_for_house: Self::get_cells_for_house_from_grid(grid),
             houses_for_cell: Self::get_houses_for_cell_from_grid(grid),
             mask_for_digit: Self::get_mask_for_digit_from_grid(grid),
-            digits_in_mask: Self::get_digits_in_mask_from_grid(grid),
-            possible_guesses_for_mask: Self::get_possible_guesses_for_mask_from_grid(grid),
             neighbours_for_cell: Self::get_neighbours_for_cell_from_grid(grid),
             start_state: BoardState::for_empty_grid(grid),
         };
@@ -121,6 +218,8 @@ impl BruteForceSolver {
             solution_count: 0,
             placement_queue: Vec::new(),
             guess_stack: Vec::new(),
+            constraints: Vec::new(),
+            rng: StdRng::from_rng(thread_rng()).unwrap(),
         }
     }
 
@@ -134,8 +233,6 @@ impl BruteForceSolver {
             cells_for_house: Self::get_cells_for_house_from_grid(grid),
             houses_for_cell: Self::get_houses_for_cell_from_grid(grid),
             mask_for_digit: Self::get_mask_for_digit_from_grid(grid),
-            digits_in_mask: Self::get_digits_in_mask_from_grid(grid),
-            possible_guesses_for_mask: Self::get_possible_guesses_for_mask_from_grid(grid),
             neighbours_for_cell: Self::get_neighbours_for_cell_from_grid(grid),
             start_state: BoardState::for_starting_grid(grid),
         };
@@ -151,27 +248,26 @@ impl BruteForceSolver {
             solution_count: 0,
             placement_queue: Vec::new(),
             guess_stack: Vec::new(),
+            constraints: Vec::new(),
+            rng: StdRng::from_rng(thread_rng()).unwrap(),
         }
     }
 
-    pub fn has_unique_solution(&mut self, clues: &[usize]) -> bool {
-        self.run(clues, 2);
-        self.solution_count == 1
-    }
-
-    pub fn has_any_solution(&mut self, clues: &[usize]) -> bool {
-        self.run(clues, 1);
-        self.solution_count > 0
+    /// Use the given random number generator to make the choices of which digits to guess, so that
+    /// random solutions can be reproduced.
+    pub fn with_rng<R: Rng>(mut self, rng: R) -> BruteForceSolver {
+        self.rng = StdRng::from_rng(rng).unwrap();
+        self
     }
 
-    pub fn count_solutions(&mut self, clues: &[usize]) -> usize {
-        self.run(clues, usize::max_value());
-        self.solution_count
+    /// Enforce the given constraint as well as the regions and neighbours of the grid.
+    pub fn with_constraint<C: Constraint + 'static>(mut self, constraint: C) -> BruteForceSolver {
+        self.constraints.push(Box::new(constraint));
+        self
     }
 
-    pub fn random_solution(&mut self, clues: &[usize]) -> Option<Vec<usize>> {
-        self.run(clues, 1);
-        if self.solution_count > 0 { Some(self.board.solution.iter().map(|x| x.trailing_zeros() as usize + 1).collect()) } else { None }
+    fn current_solution(&self) -> Vec<usize> {
+        self.board.solution.iter().map(|x| x.trailing_zeros() as usize + 1).collect()
     }
 
     fn reset(&mut self) {
@@ -185,7 +281,7 @@ impl BruteForceSolver {
 
         let mut placements = Vec::new();
         for (cell, &mask) in self.board.cells.iter().enumerate() {
-            let remaining = self.constants.digits_in_mask[mask];
+            let remaining = digits_in_mask(mask);
             if remaining == 1 { placements.push((cell, mask)); }
             else if remaining == 0 { self.invalid = true; }
         }
@@ -205,8 +301,21 @@ impl BruteForceSolver {
 
     fn run(&mut self, clues: &[usize], max_solutions: usize) {
         self.prepare_with_clues(clues);
+        while self.solution_count < max_solutions && self.find_next_solution() {}
+    }
+
+    /// Carry on searching until the next solution is found, which is then left on the board.
+    /// Returns false once there are no more solutions.
+    fn find_next_solution(&mut self) -> bool {
+        if self.board.cells_remaining == 0 && !self.invalid {
+            self.backtrack();
+        }
         while !self.finished {
             while !self.placement_queue.is_empty() && !self.invalid { self.process_queue(); }
+            if !self.constraints.is_empty() && !self.invalid {
+                self.propagate_constraints();
+                if !self.placement_queue.is_empty() && !self.invalid { continue; }
+            }
             if self.board.cells_remaining > 0 && !self.invalid {
                 self.check_hidden_singles();
                 if self.placement_queue.is_empty() { self.guess(); }
@@ -214,10 +323,10 @@ impl BruteForceSolver {
             else if self.invalid { self.backtrack(); }
             else if self.board.cells_remaining == 0 {
                 self.solution_count += 1;
-                if self.solution_count >= max_solutions { break; }
-                self.backtrack();
+                return true;
             }
         }
+        false
     }
 
     fn process_queue(&mut self) {
@@ -229,7 +338,7 @@ impl BruteForceSolver {
                 if self.board.cells[neighbour] & placement.mask != 0 {
                     self.board.cells[neighbour] ^= placement.mask;
                     let neighbour_mask = self.board.cells[neighbour];
-                    let remaining = self.constants.digits_in_mask[neighbour_mask];
+                    let remaining = digits_in_mask(neighbour_mask);
                     if remaining == 1 { self.enqueue_placement(neighbour, neighbour_mask); }
                     else if remaining == 0 { self.invalid = true; return; }
                 }
@@ -237,6 +346,36 @@ impl BruteForceSolver {
         }
     }
 
+    /// Let the constraints narrow down the digits of each cell as far as they can, queueing up
+    /// placements for any cells left with a single digit.
+    fn propagate_constraints(&mut self) {
+        let mut masks: Vec<DigitMask> = self.board.cells.iter().zip(self.board.solution.iter())
+            .map(|(&mask, &solution)| if mask == 0 { solution } else { mask })
+            .collect();
+
+        loop {
+            let before = masks.clone();
+            for constraint in &self.constraints {
+                if !constraint.propagate(&mut masks) {
+                    self.invalid = true;
+                    return;
+                }
+            }
+            if masks == before { break; }
+        }
+
+        for (cell, &mask) in masks.iter().enumerate() {
+            if mask == 0 {
+                self.invalid = true;
+                return;
+            }
+            if self.board.cells[cell] != 0 && self.board.cells[cell] != mask {
+                self.board.cells[cell] = mask;
+                if digits_in_mask(mask) == 1 { self.enqueue_placement(cell, mask); }
+            }
+        }
+    }
+
     fn check_hidden_singles(&mut self) {
         for house in 0..self.constants.num_houses {
             let (mut at_least_once, mut more_than_once) = (0, 0);
@@ -258,7 +397,7 @@ impl BruteForceSolver {
                     let cell = self.constants.cells_for_house[house][idx];
                     let mask = self.board.cells[cell] & exactly_once;
                     if mask != 0 {
-                        if self.constants.digits_in_mask[mask] > 1 {
+                        if digits_in_mask(mask) > 1 {
                             self.invalid = true;
                             return;
                         }
@@ -273,7 +412,7 @@ impl BruteForceSolver {
     fn get_best_cell_to_guess(&mut self) -> Option<Cell> {
         let (mut best_cell, mut best_digits) = (0, self.constants.num_digits + 1);
         for cell in 0..self.constants.num_cells {
-            let digits = self.constants.digits_in_mask[self.board.cells[cell]];
+            let digits = digits_in_mask(self.board.cells[cell]);
             if digits > 1 && digits < best_digits {
                 best_cell = cell; best_digits = digits;
                 if digits == 2 { break; }
@@ -284,7 +423,7 @@ impl BruteForceSolver {
 
     fn get_guess_for_cell(&mut self, cell: Cell) -> Guess {
         let cell_mask = self.board.cells[cell];
-        let guess_mask = *thread_rng().choose(&self.constants.possible_guesses_for_mask[cell_mask]).unwrap();
+        let guess_mask = nth_digit_in_mask(cell_mask, self.rng.gen_range(0, digits_in_mask(cell_mask)));
         let leftovers = cell_mask ^ guess_mask;
         Guess { cell: cell, mask: guess_mask, remaining: leftovers }
     }
@@ -305,7 +444,7 @@ impl BruteForceSolver {
             self.board = self.board_stack.pop().unwrap().clone();
             self.placement_queue.clear();
             let guess = self.guess_stack.pop().unwrap();
-            if self.constants.digits_in_mask[guess.remaining] > 1 {
+            if digits_in_mask(guess.remaining) > 1 {
                 self.board.cells[guess.cell] = guess.remaining;
             } else {
                 self.enqueue_placement(guess.cell, guess.remaining);
@@ -344,8 +483,14 @@ impl BruteForceSolver {
         T::size()
     }
 
+    /// The regions which hold every digit exactly once. Smaller regions just stop digits from
+    /// repeating, which the neighbours of each cell already take care of.
+    fn get_houses_from_grid<T: GridSize>(grid: &Grid<T>) -> Vec<&CellSet<T>> {
+        grid.all_regions().iter().filter(|region| region.len() == T::size()).collect()
+    }
+
     fn get_num_houses_from_grid<T: GridSize>(grid: &Grid<T>) -> usize {
-        grid.all_regions().len()
+        Self::get_houses_from_grid(grid).len()
     }
 
     fn get_num_cells_from_grid<T: GridSize>(_grid: &Grid<T>) -> usize {
@@ -357,14 +502,14 @@ impl BruteForceSolver {
     }
 
     fn get_cells_for_house_from_grid<T: GridSize>(grid: &Grid<T>) -> Vec<Vec<Cell>> {
-        grid.all_regions().iter()
+        Self::get_houses_from_grid(grid).iter()
             .map(|region| region.iter().collect())
             .collect()
     }
 
     fn get_houses_for_cell_from_grid<T: GridSize>(grid: &Grid<T>) -> Vec<Vec<House>> {
         let mut houses_for_cell = vec![vec![]; T::size() * T::size()];
-        for (idx, house) in grid.all_regions().iter().enumerate() {
+        for (idx, house) in Self::get_houses_from_grid(grid).iter().enumerate() {
             for cell in house.iter() {
                 houses_for_cell[cell].push(idx);
             }
@@ -376,17 +521,69 @@ impl BruteForceSolver {
         (0..T::size() + 1).map(|digit| if digit == 0 { 0 } else { 1 << (digit - 1) }).collect()
     }
 
-    fn get_digits_in_mask_from_grid<T: GridSize>(_grid: &Grid<T>) -> Vec<usize> {
-        (0..(1 << T::size())).map(|mask: usize| mask.count_ones() as usize).collect()
+    fn get_neighbours_for_cell_from_grid<T: GridSize>(grid: &Grid<T>) -> Vec<Vec<Cell>> {
+        (0..T::size() * T::size()).map(|cell| grid.neighbours(cell).iter().collect()).collect()
     }
 
-    fn get_possible_guesses_for_mask_from_grid<T: GridSize>(grid: &Grid<T>) -> Vec<Vec<DigitMask>> {
-        let mask_for_digit = Self::get_mask_for_digit_from_grid(grid);
-        (0..(1 << T::size())).map(|mask| (0..T::size()).filter(|v| mask & (1 << v) != 0).map(|v| mask_for_digit[v + 1]).collect()).collect()
+}
+
+/// The number of digits in the given mask.
+pub fn digits_in_mask(mask: DigitMask) -> usize {
+    mask.count_ones() as usize
+}
+
+/// The mask holding just the `n`th lowest digit of the given mask, counting from zero.
+fn nth_digit_in_mask(mut mask: DigitMask, n: usize) -> DigitMask {
+    for _ in 0..n {
+        mask &= mask - 1;
     }
+    mask & mask.wrapping_neg()
+}
 
-    fn get_neighbours_for_cell_from_grid<T: GridSize>(grid: &Grid<T>) -> Vec<Vec<Cell>> {
-        (0..T::size() * T::size()).map(|cell| grid.neighbours(cell).iter().collect()).collect()
+impl BruteForce for BruteForceSolver {
+
+    fn solutions<'a>(&'a mut self, clues: &[usize], limit: usize) -> Box<dyn Iterator<Item = Vec<usize>> + 'a> {
+        self.prepare_with_clues(clues);
+        Box::new(Solutions { solver: self, remaining: limit })
     }
 
-}
\ No newline at end of file
+    fn random_solution(&mut self, clues: &[usize]) -> Option<Vec<usize>> {
+        self.run(clues, 1);
+        if self.solution_count > 0 { Some(self.current_solution()) } else { None }
+    }
+
+    /// Check whether the puzzle with the given clues has exactly one solution.
+    fn has_unique_solution(&mut self, clues: &[usize]) -> bool {
+        self.run(clues, 2);
+        self.solution_count == 1
+    }
+
+    fn has_any_solution(&mut self, clues: &[usize]) -> bool {
+        self.run(clues, 1);
+        self.solution_count > 0
+    }
+
+    fn count_solutions(&mut self, clues: &[usize]) -> usize {
+        self.run(clues, usize::max_value());
+        self.solution_count
+    }
+}
+
+/// An iterator over the solutions of a puzzle, found one at a time by a brute-force solver.
+struct Solutions<'a> {
+    solver: &'a mut BruteForceSolver,
+    remaining: usize,
+}
+
+impl <'a> Iterator for Solutions<'a> {
+
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Vec<usize>> {
+        if self.remaining == 0 || !self.solver.find_next_solution() {
+            return None;
+        }
+        self.remaining -= 1;
+        Some(self.solver.current_solution())
+    }
+}
@@ -1,6 +1,12 @@
 //! Functions relating to canonicalizing generated puzzles.
 
-use grid::GridSize;
+use grid::{Grid, GridSize};
+use grid::automorphisms::Automorphisms;
+use grid::transform::{band_preserving_orders, box_shape, Transform};
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::marker::PhantomData;
 
 /// Find the minlex variant of the given puzzle - that is, the puzzle which is formed from the
 /// given puzzle by relabelling digits in such a way that the puzzle's clues are minimal in the
@@ -25,3 +31,270 @@ pub fn minlex<T: GridSize>(puzzle: &[usize]) -> Vec<usize> {
 
     new_puzzle
 }
+
+/// The reasons that puzzles on a grid can't be canonicalized.
+pub enum CanonicalizeError {
+    /// The grid is of a size with too many symmetries to search through.
+    TooLarge(usize),
+}
+
+impl fmt::Display for CanonicalizeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::CanonicalizeError::*;
+        match *self {
+            TooLarge(size) => write!(f, "Puzzles of size {} are too large to canonicalize - the largest size is {}", size, MAX_SIZE),
+        }
+    }
+}
+
+impl fmt::Debug for CanonicalizeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self, f)
+    }
+}
+
+/// Finds canonical forms of puzzles on a particular grid, using only the symmetries of that grid's
+/// layout so that the canonical form of a puzzle is always a valid puzzle for the same variant.
+#[derive(Clone)]
+pub struct Canonicalizer<T: GridSize> {
+    automorphisms: Automorphisms,
+    size: PhantomData<T>,
+}
+
+impl<T: GridSize> Canonicalizer<T> {
+
+    /// Create a canonicalizer for puzzles on the given grid, as long as the grid isn't too large.
+    pub fn for_grid(grid: &Grid<T>) -> Result<Canonicalizer<T>, CanonicalizeError> {
+        if T::size() > MAX_SIZE {
+            return Err(CanonicalizeError::TooLarge(T::size()));
+        }
+        Ok(Canonicalizer { automorphisms: grid.automorphisms(), size: PhantomData })
+    }
+
+    /// Find the canonical form of the given puzzle, along with the transformation which takes the
+    /// puzzle to it. This is the minimal form in the lexicographic ordering over the symmetries of
+    /// the grid and relabellings of the digits.
+    pub fn canonicalize(&self, puzzle: &[usize]) -> (Vec<usize>, Transform) {
+
+        let transforms = match self.automorphisms {
+            Automorphisms::Standard => return canonicalize_standard::<T>(puzzle),
+            Automorphisms::Listed(ref transforms) => transforms,
+        };
+
+        // Try each symmetry in turn, relabelling the digits in the order they appear, and giving
+        // up on a symmetry as soon as it's worse than the best found so far.
+        let (mut best, mut best_transform): (Vec<usize>, Option<(&Transform, Vec<usize>)>) = (vec![], None);
+        let mut values = vec![0; puzzle.len()];
+        for transform in transforms {
+            let (mut labels, mut next_label) = (vec![0; T::size() + 1], 1);
+            let mut ordering = if best.is_empty() { Ordering::Less } else { Ordering::Equal };
+            for (idx, &cell) in transform.cells().iter().enumerate() {
+                let clue = puzzle[cell];
+                if clue != 0 && labels[clue] == 0 {
+                    labels[clue] = next_label;
+                    next_label += 1;
+                }
+                values[idx] = labels[clue];
+                if ordering == Ordering::Equal {
+                    ordering = values[idx].cmp(&best[idx]);
+                    if ordering == Ordering::Greater { break; }
+                }
+            }
+            if ordering == Ordering::Less {
+                best.clone_from(&values);
+                best_transform = Some((transform, labels));
+            }
+        }
+
+        // Any digits which don't appear in the puzzle are relabelled in order, so that the
+        // transform can still be applied to a solution.
+        let (transform, mut digits) = best_transform.unwrap();
+        let next_label = digits.iter().max().unwrap() + 1;
+        for (digit, label) in digits.iter_mut().skip(1).filter(|digit| **digit == 0).zip(next_label..) {
+            *digit = label;
+        }
+        (best, Transform::new(transform.cells().to_vec(), digits))
+    }
+}
+
+/// The largest grid size that can be canonicalized. Beyond this, the orderings of the rows and
+/// columns are too many to keep every choice that ties for the smallest rows so far - an empty
+/// 16x16 puzzle ties on all of them.
+const MAX_SIZE: usize = 9;
+
+/// Find the canonical form of the given puzzle under the symmetries of a standard Sudoku of its
+/// size - permuting rows within bands, bands, columns within stacks and stacks, transposing when
+/// the boxes are square, and relabelling digits. The canonical form is the one which is minimal
+/// in the lexicographic ordering, with empty cells first, so two puzzles have the same canonical
+/// form exactly when one can be transformed into the other.
+///
+/// Also returns the transformation which takes the puzzle to its canonical form. Grids larger
+/// than 9x9 can't be canonicalized.
+pub fn canonicalize<T: GridSize>(puzzle: &[usize]) -> Result<(Vec<usize>, Transform), CanonicalizeError> {
+    if T::size() > MAX_SIZE {
+        return Err(CanonicalizeError::TooLarge(T::size()));
+    }
+    Ok(canonicalize_standard::<T>(puzzle))
+}
+
+/// Find the canonical form of a puzzle no larger than `MAX_SIZE` under the symmetries of a
+/// standard Sudoku.
+fn canonicalize_standard<T: GridSize>(puzzle: &[usize]) -> (Vec<usize>, Transform) {
+
+    let size = T::size();
+    let (height, width) = box_shape(size);
+
+    // The puzzle as it is, and transposed if that is a symmetry, along with the original cell for
+    // each cell of the reoriented puzzle.
+    let mut orientations = vec![(0..size * size).collect::<Vec<usize>>()];
+    if height == width {
+        orientations.push((0..size * size).map(|cell| (cell % size) * size + cell / size).collect());
+    }
+    let oriented_puzzles: Vec<Vec<usize>> = orientations.iter().map(|cells| cells.iter().map(|&cell| puzzle[cell]).collect()).collect();
+
+    // Every ordering of the columns which keeps the columns of each stack together.
+    let column_orders = band_preserving_orders(size, width);
+
+    // Build up the canonical form one row at a time, keeping every choice of rows and columns which
+    // gives the smallest rows so far.
+    let mut candidates: Vec<Candidate> = Vec::new();
+    for orientation in 0..orientations.len() {
+        for column_order in 0..column_orders.len() {
+            candidates.push(Candidate { orientation, column_order, rows: [0; MAX_SIZE], labels: [0; MAX_SIZE + 1], next_label: 1 });
+        }
+    }
+
+    let mut canonical = Vec::new();
+    let (mut best_row, mut row_values) = ([0; MAX_SIZE], [0; MAX_SIZE]);
+    for row_idx in 0..size {
+        let mut next_candidates = Vec::new();
+        for candidate in &candidates {
+            for row in candidate.next_rows(row_idx, size, height) {
+
+                // Work out the row given by this choice, giving up as soon as it's worse than the
+                // best one found so far.
+                let mut next = *candidate;
+                let mut ordering = if next_candidates.is_empty() { Ordering::Less } else { Ordering::Equal };
+                for (idx, &column) in column_orders[next.column_order].iter().enumerate() {
+                    let value = oriented_puzzles[next.orientation][row * size + column];
+                    if value != 0 && next.labels[value] == 0 {
+                        next.labels[value] = next.next_label;
+                        next.next_label += 1;
+                    }
+                    row_values[idx] = next.labels[value];
+                    if ordering == Ordering::Equal {
+                        ordering = row_values[idx].cmp(&best_row[idx]);
+                        if ordering == Ordering::Greater { break; }
+                    }
+                }
+
+                match ordering {
+                    Ordering::Greater => continue,
+                    Ordering::Less => {
+                        best_row = row_values;
+                        next_candidates.clear();
+                    },
+                    Ordering::Equal => (),
+                }
+                next.rows[row_idx] = row as u8;
+                next_candidates.push(next);
+            }
+        }
+        canonical.extend(best_row[..size].iter().map(|&value| value as usize));
+        candidates = next_candidates;
+    }
+
+    // Any digits which don't appear in the puzzle are relabelled in order, so that the transform
+    // can still be applied to a solution.
+    let best = &candidates[0];
+    let mut digits: Vec<usize> = best.labels[..size + 1].iter().map(|&label| label as usize).collect();
+    for (digit, label) in digits.iter_mut().skip(1).filter(|digit| **digit == 0).zip(best.next_label as usize..) {
+        *digit = label;
+    }
+    let columns = &column_orders[best.column_order];
+    let cells = (0..size * size).map(|cell| orientations[best.orientation][best.rows[cell / size] as usize * size + columns[cell % size]]).collect();
+
+    (canonical, Transform::new(cells, digits))
+}
+
+/// A partial choice of the transformation to a canonical form, small enough to be copied freely.
+#[derive(Clone, Copy)]
+struct Candidate {
+    orientation: usize,
+    column_order: usize,
+    rows: [u8; MAX_SIZE],
+    labels: [u8; MAX_SIZE + 1],
+    next_label: u8,
+}
+
+impl Candidate {
+
+    /// The rows which could come next - any row of an unused band when starting a new band, and
+    /// otherwise any unused row of the current band.
+    fn next_rows(&self, row_idx: usize, size: usize, height: usize) -> impl Iterator<Item = usize> + '_ {
+        let used = &self.rows[..row_idx];
+        let band_start = row_idx - row_idx % height;
+        (0..size).filter(move |&row| if row_idx == band_start {
+            used.iter().all(|&used| used as usize / height != row / height)
+        } else {
+            row / height == used[band_start] as usize / height && !used.contains(&(row as u8))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use grid::variants::{empty_diagonal, empty_sixteen_by_sixteen, Grid16, Grid9};
+    use super::*;
+
+    #[test]
+    fn test_equivalent_puzzles_have_same_canonical_form() {
+        let puzzle: Vec<usize> = ".9.7..4..1..6...7.6...3..8.85...6.....6...3.....4...58.4..2...6.3...1..2..2..3.9."
+            .chars().map(|c| c.to_digit(10).unwrap_or(0) as usize).collect();
+
+        // Swap the first and last bands, swap two columns within a stack, transpose and relabel.
+        let swap_bands = |cell: usize| ((cell / 9 + 6) % 9) * 9 + cell % 9;
+        let swap_columns = |cell: usize| match cell % 9 { 4 => cell + 1, 5 => cell - 1, _ => cell };
+        let transform = Transform::new(
+            (0..81).map(|cell| swap_columns(swap_bands((cell % 9) * 9 + cell / 9))).collect(),
+            (0..10).map(|digit| if digit == 0 { 0 } else { 10 - digit }).collect(),
+        );
+        let transformed = transform.apply(&puzzle);
+        assert_ne!(transformed, puzzle);
+
+        let (canonical, to_canonical) = canonicalize::<Grid9>(&puzzle).unwrap();
+        assert_eq!(canonicalize::<Grid9>(&transformed).unwrap().0, canonical);
+        assert_eq!(to_canonical.apply(&puzzle), canonical);
+        assert_eq!(to_canonical.inverse().apply(&canonical), puzzle);
+        assert_eq!(transform.then(&transform.inverse()), Transform::identity::<Grid9>());
+    }
+
+    #[test]
+    fn test_variant_canonical_form_keeps_layout() {
+        let grid = empty_diagonal();
+        let mut puzzle = vec![0; 81];
+        for &(cell, clue) in &[(0, 1), (10, 2), (30, 3), (80, 7), (8, 4), (24, 5), (56, 6), (5, 8)] {
+            puzzle[cell] = clue;
+        }
+        let rotated: Vec<usize> = puzzle.iter().rev().cloned().collect();
+
+        let canonicalizer = Canonicalizer::for_grid(&grid).unwrap();
+        let (canonical, transform) = canonicalizer.canonicalize(&puzzle);
+        assert_eq!(canonicalizer.canonicalize(&rotated).0, canonical);
+        assert_eq!(transform.apply(&puzzle), canonical);
+
+        // The clues on the diagonals must stay on the diagonals.
+        let on_diagonal = |cell: usize| cell.is_multiple_of(10) || (cell.is_multiple_of(8) && cell > 0 && cell < 80);
+        assert_eq!(canonical.iter().enumerate().filter(|&(cell, &clue)| clue != 0 && on_diagonal(cell)).count(), 7);
+    }
+
+    #[test]
+    fn test_large_grids_are_refused() {
+        let mut puzzle = vec![0; 256];
+        puzzle[0] = 1;
+        assert!(canonicalize::<Grid16>(&puzzle).is_err());
+        assert!(canonicalize::<Grid16>(&vec![0; 256]).is_err());
+        assert!(Canonicalizer::for_grid(&empty_sixteen_by_sixteen()).is_err());
+    }
+}
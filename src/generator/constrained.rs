@@ -0,0 +1,170 @@
+//! Processes to generate killer and thermo Sudoku puzzles, by drawing cages or thermometers onto
+//! a random solution and then removing as many clues as the extra constraints allow.
+
+use rand::prelude::*;
+
+use generator::brute_force::{BruteForce, BruteForceSolver};
+use generator::constraints::{KillerCage, Thermometer};
+use generator::symmetry::{remove_clues, Symmetry};
+use grid::{Grid, GridSize};
+
+/// The largest number of cells in a generated killer cage.
+const MAX_CAGE_SIZE: usize = 4;
+
+/// The fewest cells in a generated thermometer.
+const MIN_THERMOMETER_LENGTH: usize = 3;
+
+/// A killer Sudoku puzzle - its cages, together with any clues still needed to make it unique.
+#[derive(Clone, Debug)]
+pub struct KillerPuzzle {
+    pub cages: Vec<KillerCage>,
+    pub clues: Vec<usize>,
+}
+
+/// A thermo Sudoku puzzle - its thermometers, together with the clues needed to make it unique.
+#[derive(Clone, Debug)]
+pub struct ThermoPuzzle {
+    pub thermometers: Vec<Thermometer>,
+    pub clues: Vec<usize>,
+}
+
+/// A structure which produces killer puzzles by splitting a random solution into cages.
+pub struct KillerPuzzlesIterator<T: GridSize> {
+    empty_grid: Grid<T>,
+    brute_force_solver: BruteForceSolver,
+    rng: StdRng,
+}
+
+impl <T: GridSize> KillerPuzzlesIterator<T> {
+
+    /// An iterator over killer puzzles on the given empty grid, using the given random number
+    /// generator.
+    pub fn for_empty_grid<R: Rng>(empty_grid: Grid<T>, mut rng: R) -> KillerPuzzlesIterator<T> {
+        let mut rng = StdRng::from_rng(&mut rng).unwrap();
+        let brute_force_solver = BruteForceSolver::for_empty_grid(&empty_grid).with_rng(&mut rng);
+        KillerPuzzlesIterator { empty_grid, brute_force_solver, rng }
+    }
+
+    /// Split the solution into cages of neighbouring cells, growing each from a random cell in a
+    /// random direction until it reaches a random size or runs into a repeated digit.
+    fn random_cages(&mut self, solution: &[usize]) -> Vec<KillerCage> {
+
+        let mut cells: Vec<usize> = (0..solution.len()).collect();
+        self.rng.shuffle(&mut cells);
+        let mut caged = vec![false; solution.len()];
+        let mut cages = Vec::new();
+
+        for start in cells {
+            if caged[start] { continue; }
+            caged[start] = true;
+            let mut cage = vec![start];
+            let size = self.rng.gen_range(2, MAX_CAGE_SIZE + 1);
+            while cage.len() < size {
+                let choices: Vec<usize> = cage.iter()
+                    .flat_map(|&cell| adjacent_cells::<T>(cell, false))
+                    .filter(|&cell| !caged[cell] && cage.iter().all(|&caged_cell| solution[caged_cell] != solution[cell]))
+                    .collect();
+                match self.rng.choose(&choices) {
+                    Some(&cell) => { caged[cell] = true; cage.push(cell); },
+                    None => break,
+                }
+            }
+            cage.sort();
+            let sum = cage.iter().map(|&cell| solution[cell]).sum();
+            cages.push(KillerCage { cells: cage, sum });
+        }
+
+        cages
+    }
+}
+
+impl <T: GridSize> Iterator for KillerPuzzlesIterator<T> {
+
+    type Item = KillerPuzzle;
+
+    fn next(&mut self) -> Option<KillerPuzzle> {
+        let solution = self.brute_force_solver.random_solution(&vec![0; T::size() * T::size()])?;
+        let cages = self.random_cages(&solution);
+        let mut solver = cages.iter().cloned().fold(BruteForceSolver::for_empty_grid(&self.empty_grid).with_rng(&mut self.rng), |solver, cage| solver.with_constraint(cage));
+        let clues = remove_clues(&mut solver, solution, Symmetry::None.orbits::<T>(), &mut self.rng);
+        Some(KillerPuzzle { cages, clues })
+    }
+}
+
+/// A structure which produces thermo puzzles by tracing thermometers along increasing digits of a
+/// random solution.
+pub struct ThermoPuzzlesIterator<T: GridSize> {
+    empty_grid: Grid<T>,
+    brute_force_solver: BruteForceSolver,
+    rng: StdRng,
+}
+
+impl <T: GridSize> ThermoPuzzlesIterator<T> {
+
+    /// An iterator over thermo puzzles on the given empty grid, using the given random number
+    /// generator.
+    pub fn for_empty_grid<R: Rng>(empty_grid: Grid<T>, mut rng: R) -> ThermoPuzzlesIterator<T> {
+        let mut rng = StdRng::from_rng(&mut rng).unwrap();
+        let brute_force_solver = BruteForceSolver::for_empty_grid(&empty_grid).with_rng(&mut rng);
+        ThermoPuzzlesIterator { empty_grid, brute_force_solver, rng }
+    }
+
+    /// Trace up to one thermometer per digit through the solution, each starting from a random
+    /// cell and stepping to a random touching cell with a higher digit until it can go no further.
+    /// Thermometers never cross, and short ones are thrown away.
+    fn random_thermometers(&mut self, solution: &[usize]) -> Vec<Thermometer> {
+
+        let mut cells: Vec<usize> = (0..solution.len()).collect();
+        self.rng.shuffle(&mut cells);
+        let mut used = vec![false; solution.len()];
+        let mut thermometers = Vec::new();
+
+        for start in cells {
+            if used[start] || thermometers.len() == T::size() { continue; }
+            let mut path = vec![start];
+            loop {
+                let last = *path.last().unwrap();
+                let choices: Vec<usize> = adjacent_cells::<T>(last, true)
+                    .filter(|&cell| !used[cell] && !path.contains(&cell) && solution[cell] > solution[last])
+                    .collect();
+                match self.rng.choose(&choices) {
+                    Some(&cell) => path.push(cell),
+                    None => break,
+                }
+            }
+            if path.len() >= MIN_THERMOMETER_LENGTH {
+                for &cell in &path { used[cell] = true; }
+                thermometers.push(Thermometer { cells: path });
+            }
+        }
+
+        thermometers
+    }
+}
+
+impl <T: GridSize> Iterator for ThermoPuzzlesIterator<T> {
+
+    type Item = ThermoPuzzle;
+
+    fn next(&mut self) -> Option<ThermoPuzzle> {
+        let solution = self.brute_force_solver.random_solution(&vec![0; T::size() * T::size()])?;
+        let thermometers = self.random_thermometers(&solution);
+        let mut solver = thermometers.iter().cloned().fold(BruteForceSolver::for_empty_grid(&self.empty_grid).with_rng(&mut self.rng), |solver, thermometer| solver.with_constraint(thermometer));
+        let clues = remove_clues(&mut solver, solution, Symmetry::None.orbits::<T>(), &mut self.rng);
+        Some(ThermoPuzzle { thermometers, clues })
+    }
+}
+
+/// The cells next to the given cell, either orthogonally or also diagonally.
+fn adjacent_cells<T: GridSize>(cell: usize, diagonals: bool) -> impl Iterator<Item = usize> {
+    let (size, row, column) = (T::size() as isize, (cell / T::size()) as isize, (cell % T::size()) as isize);
+    let offsets: &[(isize, isize)] = if diagonals {
+        &[(-1, -1), (-1, 0), (-1, 1), (0, -1), (0, 1), (1, -1), (1, 0), (1, 1)]
+    } else {
+        &[(-1, 0), (0, -1), (0, 1), (1, 0)]
+    };
+    offsets.iter()
+        .map(move |&(row_offset, column_offset)| (row + row_offset, column + column_offset))
+        .filter(move |&(row, column)| row >= 0 && row < size && column >= 0 && column < size)
+        .map(move |(row, column)| (row * size + column) as usize)
+}
@@ -0,0 +1,92 @@
+//! Constraints from Sudoku variants which go beyond regions and neighbours, for the brute-force
+//! solver to enforce.
+
+use generator::brute_force::{digits_in_mask, Constraint, DigitMask};
+
+/// A killer cage - a group of cells whose digits don't repeat and add up to the given sum.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct KillerCage {
+    pub cells: Vec<usize>,
+    pub sum: usize,
+}
+
+impl Constraint for KillerCage {
+
+    fn propagate(&self, masks: &mut [DigitMask]) -> bool {
+
+        // A digit placed in one cell of the cage can't appear anywhere else in it.
+        for &cell in &self.cells {
+            if digits_in_mask(masks[cell]) == 1 {
+                let placed = masks[cell];
+                for &other in self.cells.iter().filter(|&&other| other != cell) {
+                    masks[other] &= !placed;
+                    if masks[other] == 0 { return false; }
+                }
+            }
+        }
+
+        // Each digit of a cell must leave a total that the other cells can make up between them.
+        for &cell in &self.cells {
+            let others: Vec<DigitMask> = self.cells.iter().filter(|&&other| other != cell).map(|&other| masks[other]).collect();
+            let mut mask = masks[cell];
+            for digit in digits(masks[cell]) {
+                let (min, max) = sum_bounds(&others, 1 << (digit - 1));
+                if digit > self.sum || self.sum - digit < min || self.sum - digit > max {
+                    mask &= !(1 << (digit - 1));
+                }
+            }
+            if mask == 0 { return false; }
+            masks[cell] = mask;
+        }
+
+        true
+    }
+}
+
+/// A thermometer - a path of cells, starting from the bulb, whose digits strictly increase along
+/// the path.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Thermometer {
+    pub cells: Vec<usize>,
+}
+
+impl Constraint for Thermometer {
+
+    fn propagate(&self, masks: &mut [DigitMask]) -> bool {
+
+        // Each cell must be above the lowest digit of the cell before it...
+        let mut lowest = 0;
+        for &cell in &self.cells {
+            masks[cell] &= !((1 << lowest) - 1);
+            if masks[cell] == 0 { return false; }
+            lowest = masks[cell].trailing_zeros() as usize + 1;
+        }
+
+        // ...and below the highest digit of the cell after it.
+        let mut highest = DigitMask::BITS as usize;
+        for &cell in self.cells.iter().rev() {
+            masks[cell] &= (1 << (highest - 1)) - 1;
+            if masks[cell] == 0 { return false; }
+            highest = (DigitMask::BITS - masks[cell].leading_zeros()) as usize;
+        }
+
+        true
+    }
+}
+
+/// The digits in the given mask, in increasing order.
+fn digits(mask: DigitMask) -> impl Iterator<Item = usize> {
+    (0..DigitMask::BITS as usize).filter(move |bit| mask & (1 << bit) != 0).map(|bit| bit + 1)
+}
+
+/// The smallest and largest totals that cells with the given masks could add up to without
+/// repeating a digit or using the excluded digits.
+fn sum_bounds(masks: &[DigitMask], excluded: DigitMask) -> (usize, usize) {
+    let available: Vec<usize> = digits(masks.iter().fold(0, |union, &mask| union | mask) & !excluded).collect();
+    if available.len() < masks.len() {
+        return (usize::MAX, 0);
+    }
+    let min = available.iter().take(masks.len()).sum();
+    let max = available.iter().rev().take(masks.len()).sum();
+    (min, max)
+}
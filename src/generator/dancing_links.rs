@@ -0,0 +1,416 @@
+//! A brute-force solver which treats a puzzle as an exact cover problem, and solves it with
+//! Knuth's Algorithm X using dancing links.
+//!
+//! Each row of the problem places a digit in a cell. Every cell must hold exactly one digit and
+//! every region as large as the grid must hold each digit exactly once, so these are the primary
+//! columns which must each be covered once. Smaller regions and neighbours which share no region
+//! may hold each digit at most once, so they are secondary columns which may be left uncovered.
+//! Nothing about the layout is assumed beyond the regions and neighbours of the grid.
+
+use rand::prelude::*;
+
+use generator::brute_force::BruteForce;
+use grid::{Grid, GridSize};
+
+/// The node at the head of the list of primary columns still to be covered.
+const ROOT: usize = 0;
+
+/// A brute-force solver built on dancing links, for any layout of regions and neighbours.
+pub struct DancingLinksSolver {
+
+    /// The number of digits, and so of cells along each side of the grid.
+    num_digits: usize,
+
+    /// The links between nodes. The root comes first, followed by a header for each column and
+    /// then the nodes of each row in turn.
+    left: Vec<usize>,
+    right: Vec<usize>,
+    up: Vec<usize>,
+    down: Vec<usize>,
+
+    /// The column header of each node.
+    column: Vec<usize>,
+
+    /// The number of rows still in each column, indexed by header.
+    column_size: Vec<usize>,
+
+    /// Whether each column, indexed by header, has been covered.
+    covered: Vec<bool>,
+
+    /// The cell and digit placed by the row of each node.
+    placement: Vec<(usize, usize)>,
+
+    /// For each cell and digit, a node of the row which places the digit there, if the digit is
+    /// allowed at all.
+    row_for_placement: Vec<Option<usize>>,
+
+    /// Whether to try the rows of each column in a random order.
+    randomise: bool,
+
+    rng: StdRng,
+
+    /// The rows selected to place the clues of the current puzzle.
+    clue_rows: Vec<usize>,
+
+    /// The columns covered by the search so far, each with the rows that could cover it.
+    frames: Vec<Frame>,
+
+    /// The rows chosen by the search so far, one for each frame.
+    chosen: Vec<usize>,
+
+    /// Whether the rows chosen so far make up the last solution found.
+    at_solution: bool,
+
+    /// Whether every solution of the current puzzle has been found.
+    finished: bool,
+}
+
+/// A column covered during the search, along with the rows which could cover it in the order they
+/// are tried. The row before `next` is the one currently selected, if any.
+struct Frame {
+    header: usize,
+    rows: Vec<usize>,
+    next: usize,
+}
+
+impl DancingLinksSolver {
+
+    /// A solver for puzzles on the given empty grid.
+    pub fn for_empty_grid<T: GridSize>(grid: &Grid<T>) -> DancingLinksSolver {
+        DancingLinksSolver::new(grid, |_, _| true)
+    }
+
+    /// A solver for puzzles with the given starting grid, whose values and candidates restrict
+    /// the digits that each cell can hold.
+    pub fn for_starting_grid<T: GridSize>(grid: &Grid<T>) -> DancingLinksSolver {
+        DancingLinksSolver::new(grid, |cell, digit| grid.value(cell) == Some(digit) || grid.has_candidate(cell, digit))
+    }
+
+    /// Use the given random number generator to choose the order in which rows are tried when
+    /// looking for a random solution.
+    pub fn with_rng<R: Rng>(mut self, rng: R) -> DancingLinksSolver {
+        self.rng = StdRng::from_rng(rng).unwrap();
+        self
+    }
+
+    fn new<T: GridSize, F: Fn(usize, usize) -> bool>(grid: &Grid<T>, allowed: F) -> DancingLinksSolver {
+
+        let (size, num_cells) = (T::size(), T::size() * T::size());
+
+        // Each column is a set of cells, and is either primary or secondary. A cell column covers
+        // its cell whatever the digit, while the others cover just one digit of their cells.
+        let mut groups: Vec<(Vec<usize>, bool)> = Vec::new();
+        for region in grid.all_regions() {
+            groups.push((region.iter().collect(), region.len() == size));
+        }
+        for cell in 0..num_cells {
+            for neighbour in grid.neighbours(cell).iter().filter(|&neighbour| neighbour > cell) {
+                if !grid.all_regions().iter().any(|region| region.contains(cell) && region.contains(neighbour)) {
+                    groups.push((vec![cell, neighbour], false));
+                }
+            }
+        }
+
+        let num_columns = num_cells + groups.len() * size;
+        let mut solver = DancingLinksSolver {
+            num_digits: size,
+            left: (0..num_columns + 1).map(|node| if node == 0 { num_columns } else { node - 1 }).collect(),
+            right: (0..num_columns + 1).map(|node| if node == num_columns { 0 } else { node + 1 }).collect(),
+            up: (0..num_columns + 1).collect(),
+            down: (0..num_columns + 1).collect(),
+            column: (0..num_columns + 1).collect(),
+            column_size: vec![0; num_columns + 1],
+            covered: vec![false; num_columns + 1],
+            placement: vec![(0, 0); num_columns + 1],
+            row_for_placement: vec![None; num_cells * size],
+            randomise: false,
+            rng: StdRng::from_rng(thread_rng()).unwrap(),
+            clue_rows: Vec::new(),
+            frames: Vec::new(),
+            chosen: Vec::new(),
+            at_solution: false,
+            finished: true,
+        };
+
+        // Secondary columns are left out of the list of columns still to be covered.
+        let cell_header = |cell: usize| 1 + cell;
+        let group_header = |group: usize, digit: usize| 1 + num_cells + group * size + digit - 1;
+        for (group, &(_, primary)) in groups.iter().enumerate() {
+            if primary { continue; }
+            for digit in 1..size + 1 {
+                let header = group_header(group, digit);
+                let (previous, next) = (solver.left[header], solver.right[header]);
+                solver.right[previous] = next;
+                solver.left[next] = previous;
+                solver.left[header] = header;
+                solver.right[header] = header;
+            }
+        }
+
+        let mut groups_for_cell = vec![vec![]; num_cells];
+        for (group, (cells, _)) in groups.iter().enumerate() {
+            for &cell in cells {
+                groups_for_cell[cell].push(group);
+            }
+        }
+
+        for (cell, cell_groups) in groups_for_cell.iter().enumerate() {
+            for digit in (1..size + 1).filter(|&digit| allowed(cell, digit)) {
+                let headers: Vec<usize> = Some(cell_header(cell)).into_iter()
+                    .chain(cell_groups.iter().map(|&group| group_header(group, digit)))
+                    .collect();
+                let first = solver.left.len();
+                solver.row_for_placement[cell * size + digit - 1] = Some(first);
+                for (idx, &header) in headers.iter().enumerate() {
+                    let node = first + idx;
+                    solver.left.push(if idx == 0 { first + headers.len() - 1 } else { node - 1 });
+                    solver.right.push(if idx == headers.len() - 1 { first } else { node + 1 });
+                    solver.up.push(solver.up[header]);
+                    solver.down.push(header);
+                    let bottom = solver.up[header];
+                    solver.down[bottom] = node;
+                    solver.up[header] = node;
+                    solver.column.push(header);
+                    solver.column_size[header] += 1;
+                    solver.covered.push(false);
+                    solver.placement.push((cell, digit));
+                }
+            }
+        }
+
+        solver
+    }
+
+    /// Remove a column from the list of columns still to be covered, along with every row which
+    /// would cover it.
+    fn cover(&mut self, header: usize) {
+        let (left, right) = (self.left[header], self.right[header]);
+        self.right[left] = right;
+        self.left[right] = left;
+        self.covered[header] = true;
+        let mut row = self.down[header];
+        while row != header {
+            let mut node = self.right[row];
+            while node != row {
+                let (up, down) = (self.up[node], self.down[node]);
+                self.down[up] = down;
+                self.up[down] = up;
+                self.column_size[self.column[node]] -= 1;
+                node = self.right[node];
+            }
+            row = self.down[row];
+        }
+    }
+
+    /// Undo covering a column. Columns must be uncovered in the reverse order to that in which
+    /// they were covered.
+    fn uncover(&mut self, header: usize) {
+        let mut row = self.up[header];
+        while row != header {
+            let mut node = self.left[row];
+            while node != row {
+                let (up, down) = (self.up[node], self.down[node]);
+                self.down[up] = node;
+                self.up[down] = node;
+                self.column_size[self.column[node]] += 1;
+                node = self.left[node];
+            }
+            row = self.up[row];
+        }
+        let (left, right) = (self.left[header], self.right[header]);
+        self.right[left] = header;
+        self.left[right] = header;
+        self.covered[header] = false;
+    }
+
+    /// Cover every column of the given row, other than the one it was reached from.
+    fn select(&mut self, row: usize) {
+        let mut node = self.right[row];
+        while node != row {
+            let header = self.column[node];
+            self.cover(header);
+            node = self.right[node];
+        }
+    }
+
+    /// Undo selecting the given row.
+    fn deselect(&mut self, row: usize) {
+        let mut node = self.left[row];
+        while node != row {
+            let header = self.column[node];
+            self.uncover(header);
+            node = self.left[node];
+        }
+    }
+
+    /// Put back everything covered by the previous search, and then place the given clues ready
+    /// to search for the solutions of the puzzle.
+    fn prepare_with_clues(&mut self, clues: &[usize]) {
+
+        self.reset();
+
+        // A clue whose row has already been removed by an earlier clue contradicts it.
+        self.finished = false;
+        for (cell, &clue) in clues.iter().enumerate().filter(|&(_, &clue)| clue != 0) {
+            match self.row_for_placement[cell * self.num_digits + clue - 1] {
+                Some(row) if !self.covered[self.column[row]] && self.row_is_available(row) => {
+                    self.cover(self.column[row]);
+                    self.select(row);
+                    self.clue_rows.push(row);
+                },
+                _ => { self.finished = true; break; },
+            }
+        }
+    }
+
+    /// Undo the search so far and the placing of the clues, in the reverse order to that in which
+    /// they were done.
+    fn reset(&mut self) {
+        while let Some(frame) = self.frames.pop() {
+            if frame.next > 0 {
+                self.deselect(frame.rows[frame.next - 1]);
+            }
+            self.uncover(frame.header);
+        }
+        self.chosen.clear();
+        while let Some(row) = self.clue_rows.pop() {
+            self.deselect(row);
+            self.uncover(self.column[row]);
+        }
+        self.at_solution = false;
+        self.finished = true;
+    }
+
+    /// Carry on searching from where the last search stopped until the next exact cover is found,
+    /// which is then left selected. Returns false once there are no more.
+    fn find_next_solution(&mut self) -> bool {
+
+        if self.finished {
+            return false;
+        }
+
+        // Having just found a solution, move straight on to the next row of the last column.
+        let mut descend = !self.at_solution;
+        self.at_solution = false;
+
+        loop {
+            if descend {
+                if self.right[ROOT] == ROOT {
+                    self.at_solution = true;
+                    return true;
+                }
+
+                // Cover the column with the fewest rows, unless one can't be covered at all.
+                let mut best = self.right[ROOT];
+                let mut header = self.right[best];
+                while header != ROOT {
+                    if self.column_size[header] < self.column_size[best] { best = header; }
+                    header = self.right[header];
+                }
+                if self.column_size[best] > 0 {
+                    self.cover(best);
+                    let mut rows = Vec::with_capacity(self.column_size[best]);
+                    let mut row = self.down[best];
+                    while row != best {
+                        rows.push(row);
+                        row = self.down[row];
+                    }
+                    if self.randomise {
+                        self.rng.shuffle(&mut rows);
+                    }
+                    self.frames.push(Frame { header: best, rows, next: 0 });
+                }
+            }
+
+            // Swap the row selected for the last column for its next row, backtracking to the
+            // column before once it has run out.
+            let (header, next, row) = match self.frames.last() {
+                Some(frame) => (frame.header, frame.next, frame.rows.get(frame.next).cloned()),
+                None => {
+                    self.finished = true;
+                    return false;
+                },
+            };
+            if next > 0 {
+                let previous = self.chosen.pop().unwrap();
+                self.deselect(previous);
+            }
+            match row {
+                Some(row) => {
+                    self.frames.last_mut().unwrap().next += 1;
+                    self.chosen.push(row);
+                    self.select(row);
+                    descend = true;
+                },
+                None => {
+                    self.frames.pop();
+                    self.uncover(header);
+                    descend = false;
+                },
+            }
+        }
+    }
+
+    /// The solution given by the clues and the rows chosen by the search.
+    fn current_solution(&self) -> Vec<usize> {
+        let mut solution = vec![0; self.num_digits * self.num_digits];
+        for &row in self.clue_rows.iter().chain(self.chosen.iter()) {
+            let (cell, digit) = self.placement[row];
+            solution[cell] = digit;
+        }
+        solution
+    }
+
+    /// Whether none of the columns of the given row have been covered.
+    fn row_is_available(&self, row: usize) -> bool {
+        let mut node = self.right[row];
+        while node != row {
+            if self.covered[self.column[node]] { return false; }
+            node = self.right[node];
+        }
+        true
+    }
+}
+
+impl BruteForce for DancingLinksSolver {
+
+    fn solutions<'a>(&'a mut self, clues: &[usize], limit: usize) -> Box<dyn Iterator<Item = Vec<usize>> + 'a> {
+        self.prepare_with_clues(clues);
+        Box::new(Solutions { solver: self, remaining: limit })
+    }
+
+    fn count_solutions(&mut self, clues: &[usize]) -> usize {
+        self.prepare_with_clues(clues);
+        let mut count = 0;
+        while self.find_next_solution() {
+            count += 1;
+        }
+        count
+    }
+
+    fn random_solution(&mut self, clues: &[usize]) -> Option<Vec<usize>> {
+        self.randomise = true;
+        let solution = self.solutions(clues, 1).next();
+        self.randomise = false;
+        solution
+    }
+}
+
+/// An iterator over the solutions of a puzzle, found one at a time by a dancing links solver.
+struct Solutions<'a> {
+    solver: &'a mut DancingLinksSolver,
+    remaining: usize,
+}
+
+impl <'a> Iterator for Solutions<'a> {
+
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Vec<usize>> {
+        if self.remaining == 0 || !self.solver.find_next_solution() {
+            return None;
+        }
+        self.remaining -= 1;
+        Some(self.solver.current_solution())
+    }
+}
@@ -2,15 +2,268 @@
 
 pub mod brute_force;
 mod canonicalization;
+mod constrained;
+mod constraints;
+pub mod dancing_links;
+mod parallel;
 mod patterns;
+mod symmetry;
+mod targeted;
 
-use generator::patterns::PatternPuzzlesIterator;
+pub use generator::brute_force::{Backend, BruteForce};
+pub use generator::canonicalization::{canonicalize, minlex, CanonicalizeError, Canonicalizer};
+pub use generator::constrained::{KillerPuzzle, ThermoPuzzle};
+pub use generator::constraints::{KillerCage, Thermometer};
+pub use generator::parallel::ParallelPuzzlesIterator;
+pub use generator::patterns::PatternPuzzlesIterator;
+pub use generator::symmetry::{Symmetry, SymmetricPuzzlesIterator};
+pub use generator::targeted::{DifficultyTarget, TargetedPuzzlesIterator};
+
+use generator::constrained::{KillerPuzzlesIterator, ThermoPuzzlesIterator};
+use analyser::Criteria;
 use grid::{Grid, GridSize};
 
-pub fn generate_puzzles_on_empty_grid_with_pattern<T: GridSize>(grid: Grid<T>, pattern: Vec<usize>) -> impl Iterator<Item = Vec<usize>> {
-	PatternPuzzlesIterator::for_empty_grid_and_pattern(grid, pattern)
+use rand::{Rng, SeedableRng, StdRng};
+
+/// A random number generator seeded from the given number, so that generation can be repeated.
+pub fn seeded_rng(seed: u64) -> StdRng {
+    let mut bytes = [0; 32];
+    for (idx, byte) in bytes.iter_mut().enumerate() {
+        *byte = (seed >> (8 * (idx % 8))) as u8;
+    }
+    StdRng::from_seed(bytes)
+}
+
+pub fn generate_puzzles_on_empty_grid_with_pattern<T: GridSize, R: Rng>(grid: Grid<T>, pattern: Vec<usize>, rng: R) -> PatternPuzzlesIterator<T> {
+	PatternPuzzlesIterator::for_empty_grid_and_pattern(grid, pattern, rng)
+}
+
+pub fn generate_puzzles_for_starting_grid_with_pattern<T: GridSize, R: Rng>(grid: Grid<T>, pattern: Vec<usize>, rng: R) -> PatternPuzzlesIterator<T> {
+    PatternPuzzlesIterator::for_starting_grid_and_pattern(grid, pattern, rng)
+}
+
+/// Generate puzzles with the given pattern which meet a difficulty target, by hill-climbing
+/// towards the target through the vicinity of each puzzle.
+pub fn generate_puzzles_on_empty_grid_with_pattern_and_target<'a, T: GridSize + 'a, R: Rng>(grid: Grid<T>, pattern: Vec<usize>, target: DifficultyTarget<'a, T>, rng: R) -> TargetedPuzzlesIterator<'a, T> {
+    TargetedPuzzlesIterator::for_empty_grid_and_pattern(grid, pattern, target, rng)
+}
+
+/// Generate puzzles for a starting grid which meet a difficulty target, as for
+/// `generate_puzzles_on_empty_grid_with_pattern_and_target`.
+pub fn generate_puzzles_for_starting_grid_with_pattern_and_target<'a, T: GridSize + 'a, R: Rng>(grid: Grid<T>, pattern: Vec<usize>, target: DifficultyTarget<'a, T>, rng: R) -> TargetedPuzzlesIterator<'a, T> {
+    TargetedPuzzlesIterator::for_starting_grid_and_pattern(grid, pattern, target, rng)
+}
+
+/// Generate puzzles by filling in a random solution and removing as many clues as possible, while
+/// keeping the solution unique and the clues symmetric. If `minimal` is set, only puzzles from
+/// which no single clue can be removed are produced.
+pub fn generate_puzzles_on_empty_grid_with_symmetry<T: GridSize, R: Rng>(grid: Grid<T>, symmetry: Symmetry, minimal: bool, rng: R) -> SymmetricPuzzlesIterator<T> {
+    SymmetricPuzzlesIterator::for_empty_grid(grid, symmetry, rng).with_minimality(minimal)
+}
+
+/// Generate killer puzzles by splitting a random solution into cages, and then removing as many
+/// clues as possible while keeping the solution unique.
+pub fn generate_killer_puzzles_on_empty_grid<T: GridSize, R: Rng>(grid: Grid<T>, rng: R) -> impl Iterator<Item = KillerPuzzle> {
+    KillerPuzzlesIterator::for_empty_grid(grid, rng)
+}
+
+/// Generate thermo puzzles by tracing thermometers through a random solution, and then removing as
+/// many clues as possible while keeping the solution unique.
+pub fn generate_thermo_puzzles_on_empty_grid<T: GridSize, R: Rng>(grid: Grid<T>, rng: R) -> impl Iterator<Item = ThermoPuzzle> {
+    ThermoPuzzlesIterator::for_empty_grid(grid, rng)
+}
+
+/// Generate puzzles using several threads. Each thread draws its own seed from the given random
+/// number generator, but the order in which the threads find puzzles isn't fixed, so the output
+/// is only repeatable when a single thread is used.
+///
+/// To choose the brute-force backend or keep the ratings of the puzzles, use a
+/// `ParallelPuzzlesIterator` directly.
+pub fn generate_puzzles_on_empty_grid_with_pattern_in_parallel<T: GridSize + 'static, R: Rng>(grid: Grid<T>, pattern: Vec<usize>, criteria: Criteria<'static, T>, num_threads: usize, rng: R) -> impl Iterator<Item = Vec<usize>> {
+    ParallelPuzzlesIterator::for_empty_grid_and_pattern(grid, pattern, criteria, num_threads, rng).map(|(clues, _)| clues)
+}
+
+/// Generate puzzles for a starting grid using several threads, as for
+/// `generate_puzzles_on_empty_grid_with_pattern_in_parallel`.
+pub fn generate_puzzles_for_starting_grid_with_pattern_in_parallel<T: GridSize + 'static, R: Rng>(grid: Grid<T>, pattern: Vec<usize>, criteria: Criteria<'static, T>, num_threads: usize, rng: R) -> impl Iterator<Item = Vec<usize>> {
+    ParallelPuzzlesIterator::for_starting_grid_and_pattern(grid, pattern, criteria, num_threads, rng).map(|(clues, _)| clues)
+}
+
+/// Generate symmetric puzzles using several threads, as for
+/// `generate_puzzles_on_empty_grid_with_pattern_in_parallel`.
+pub fn generate_puzzles_on_empty_grid_with_symmetry_in_parallel<T: GridSize + 'static, R: Rng>(grid: Grid<T>, symmetry: Symmetry, minimal: bool, criteria: Criteria<'static, T>, num_threads: usize, rng: R) -> impl Iterator<Item = Vec<usize>> {
+    ParallelPuzzlesIterator::for_empty_grid_with_symmetry(grid, symmetry, minimal, criteria, num_threads, rng).map(|(clues, _)| clues)
 }
 
-pub fn generate_puzzles_for_starting_grid_with_pattern<T: GridSize>(grid: Grid<T>, pattern: Vec<usize>) -> impl Iterator<Item = Vec<usize>> {
-    PatternPuzzlesIterator::for_starting_grid_and_pattern(grid, pattern)
+#[cfg(test)]
+mod tests {
+
+    use analyser::rate;
+    use generator::brute_force::BruteForceSolver;
+    use grid::variants::{empty_antiknight, empty_six_by_six, empty_sixteen_by_sixteen, empty_windoku, empty_twenty_five_by_twenty_five, grid_from_empty_grid_and_clues};
+    use solver::SolveConfiguration;
+    use strategies::Strategy::*;
+    use super::*;
+
+    #[test]
+    fn test_same_seed_gives_same_puzzles() {
+        let pattern = vec![1, 4, 6, 9, 11, 13, 16, 19, 22, 24, 26, 29, 31, 34];
+        let generate = || generate_puzzles_on_empty_grid_with_pattern(empty_six_by_six(), pattern.clone(), seeded_rng(17)).take(2).collect::<Vec<_>>();
+        let puzzles = generate();
+        assert_eq!(puzzles.len(), 2);
+        assert_eq!(puzzles, generate());
+    }
+
+    #[test]
+    fn test_targeted_puzzles_meet_target() {
+        let pattern = vec![1, 4, 6, 9, 11, 13, 16, 19, 22, 24, 26, 29, 31, 34];
+        let configuration = SolveConfiguration::with_strategies(vec![FullHouse, NakedSingle, HiddenSingle]);
+        let target = DifficultyTarget::RatingBand(configuration.clone(), 2, 2);
+        for clues in generate_puzzles_on_empty_grid_with_pattern_and_target(empty_six_by_six(), pattern, target, seeded_rng(5)).with_backend(Backend::DancingLinks).take(2) {
+            let puzzle = grid_from_empty_grid_and_clues(&empty_six_by_six(), &clues).unwrap();
+            assert_eq!(rate(&puzzle, &configuration).unwrap().score, 2);
+        }
+    }
+
+    #[test]
+    fn test_parallel_puzzles_come_with_ratings() {
+        let configuration = SolveConfiguration::with_strategies(vec![FullHouse, NakedSingle, HiddenSingle]);
+        let criteria = Criteria::solvable_with(configuration.clone());
+        let puzzles = ParallelPuzzlesIterator::for_empty_grid_with_symmetry(empty_six_by_six(), Symmetry::Rotational, false, criteria, 2, seeded_rng(9)).with_backend(Backend::DancingLinks);
+        for (clues, rating) in puzzles.take(4) {
+            let puzzle = grid_from_empty_grid_and_clues(&empty_six_by_six(), &clues).unwrap();
+            assert_eq!(rating, rate(&puzzle, &configuration));
+            assert!(rating.is_some());
+        }
+    }
+
+    #[test]
+    fn test_sixteen_by_sixteen_puzzles() {
+        let grid = empty_sixteen_by_sixteen();
+        let mut solver = BruteForceSolver::for_empty_grid(&grid);
+        let puzzle = generate_puzzles_on_empty_grid_with_symmetry(grid.clone(), Symmetry::Rotational, false, seeded_rng(6)).next().unwrap();
+        assert!(solver.has_unique_solution(&puzzle));
+        assert!((0..256).all(|cell| (puzzle[cell] == 0) == (puzzle[255 - cell] == 0)));
+
+        // Leave out a rectangle of 1s and 9s from a solution, which can be filled in either way,
+        // and generate puzzles with clues in opposite corners of it.
+        let solution: Vec<usize> = (0..256).map(|cell| (4 * (cell / 16 % 4) + cell / 64 + cell % 16) % 16 + 1).collect();
+        let mut clues = solution.clone();
+        for &cell in &[0, 8, 32, 40] {
+            clues[cell] = 0;
+        }
+        let starting_grid = grid_from_empty_grid_and_clues(&grid, &clues).unwrap();
+        let mut puzzles: Vec<Vec<usize>> = generate_puzzles_for_starting_grid_with_pattern(starting_grid, vec![0, 40], seeded_rng(6)).take(2).collect();
+        puzzles.sort();
+        assert_eq!(puzzles.iter().map(|puzzle| (puzzle[0], puzzle[40])).collect::<Vec<_>>(), vec![(1, 1), (9, 9)]);
+        assert!(puzzles.iter().all(|puzzle| solver.has_unique_solution(puzzle)));
+    }
+
+    #[test]
+    fn test_symmetric_minimal_puzzles() {
+        let grid = empty_six_by_six();
+        let mut solver = BruteForceSolver::for_empty_grid(&grid);
+        for puzzle in generate_puzzles_on_empty_grid_with_symmetry(grid, Symmetry::Rotational, true, seeded_rng(3)).with_backend(Backend::DancingLinks).take(3) {
+            assert!(solver.has_unique_solution(&puzzle));
+            for cell in 0..36 {
+                assert_eq!(puzzle[cell] == 0, puzzle[35 - cell] == 0);
+                if puzzle[cell] != 0 {
+                    let mut reduced = puzzle.clone();
+                    reduced[cell] = 0;
+                    assert!(!solver.has_unique_solution(&reduced));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_solutions_and_backbone() {
+        let grid = empty_six_by_six();
+        let mut solver = BruteForceSolver::for_empty_grid(&grid);
+        let puzzle = generate_puzzles_on_empty_grid_with_symmetry(grid, Symmetry::None, false, seeded_rng(11)).next().unwrap();
+        let mut clues = puzzle.clone();
+        for cell in (0..36).filter(|&cell| puzzle[cell] != 0).take(4) {
+            clues[cell] = 0;
+        }
+
+        let solutions: Vec<Vec<usize>> = solver.solutions(&clues, usize::MAX).collect();
+        assert!(solutions.len() > 1);
+        assert_eq!(solutions.len(), solver.count_solutions(&clues));
+        assert!(solutions.contains(&solver.random_solution(&puzzle).unwrap()));
+        assert_eq!(solver.solutions(&clues, 1).count(), 1);
+
+        let backbone = solver.backbone(&clues);
+        for cell in 0..36 {
+            let mut digits: Vec<usize> = solutions.iter().map(|solution| solution[cell]).collect();
+            digits.sort();
+            digits.dedup();
+            assert_eq!(backbone[cell], digits);
+        }
+    }
+
+    #[test]
+    fn test_backends_agree() {
+        fn check<T: GridSize>(grid: Grid<T>, seed: u64) {
+            let puzzle = generate_puzzles_on_empty_grid_with_symmetry(grid.clone(), Symmetry::None, false, seeded_rng(seed)).next().unwrap();
+            let mut bitmask = Backend::Bitmask.for_empty_grid(&grid, seeded_rng(seed));
+            let mut dancing_links = Backend::DancingLinks.for_empty_grid(&grid, seeded_rng(seed));
+            let mut clues = puzzle.clone();
+            for cell in (0..clues.len()).filter(|&cell| puzzle[cell] != 0).take(2) {
+                clues[cell] = 0;
+                let mut expected: Vec<Vec<usize>> = bitmask.solutions(&clues, usize::MAX).collect();
+                let mut found: Vec<Vec<usize>> = dancing_links.solutions(&clues, usize::MAX).collect();
+                expected.sort();
+                found.sort();
+                assert_eq!(expected, found);
+                assert_eq!(bitmask.backbone(&clues), dancing_links.backbone(&clues));
+            }
+
+            // Stopping part way through the solutions leaves the solver ready for the next search.
+            assert!(dancing_links.solutions(&clues, usize::MAX).next().is_some());
+            assert_eq!(dancing_links.count_solutions(&clues), bitmask.count_solutions(&clues));
+
+            // Clues which clash with each other have no solutions.
+            let mut clashing = vec![0; clues.len()];
+            clashing[0] = 1;
+            clashing[grid.neighbours(0).iter().next().unwrap()] = 1;
+            assert!(!dancing_links.has_any_solution(&clashing));
+            assert!(dancing_links.random_solution(&vec![0; clues.len()]).is_some());
+        }
+        check(empty_six_by_six(), 4);
+        check(empty_antiknight(), 8);
+        check(empty_windoku(), 15);
+    }
+
+    #[test]
+    fn test_killer_and_thermo_puzzles() {
+        let grid = empty_six_by_six();
+        for puzzle in generate_killer_puzzles_on_empty_grid(grid.clone(), seeded_rng(2)).take(2) {
+            let mut solver = puzzle.cages.iter().cloned().fold(BruteForceSolver::for_empty_grid(&grid), |solver, cage| solver.with_constraint(cage));
+            assert!(solver.has_unique_solution(&puzzle.clues));
+            let solution = solver.random_solution(&puzzle.clues).unwrap();
+            assert_eq!(puzzle.cages.iter().map(|cage| cage.cells.len()).sum::<usize>(), 36);
+            assert!(puzzle.cages.iter().all(|cage| cage.cells.iter().map(|&cell| solution[cell]).sum::<usize>() == cage.sum));
+        }
+        for puzzle in generate_thermo_puzzles_on_empty_grid(grid.clone(), seeded_rng(2)).take(2) {
+            let mut solver = puzzle.thermometers.iter().cloned().fold(BruteForceSolver::for_empty_grid(&grid), |solver, thermometer| solver.with_constraint(thermometer));
+            assert!(solver.has_unique_solution(&puzzle.clues));
+            let solution = solver.random_solution(&puzzle.clues).unwrap();
+            assert!(puzzle.thermometers.iter().all(|thermometer| thermometer.cells.windows(2).all(|pair| solution[pair[0]] < solution[pair[1]])));
+        }
+    }
+
+    #[test]
+    fn test_brute_force_on_variants_and_large_grids() {
+        let grid = empty_antiknight();
+        let solution = BruteForceSolver::for_empty_grid(&grid).random_solution(&[0; 81]).unwrap();
+        assert!((0..81).all(|cell| grid.neighbours(cell).iter().all(|neighbour| solution[neighbour] != solution[cell])));
+
+        let grid = empty_twenty_five_by_twenty_five();
+        let solution = BruteForceSolver::for_empty_grid(&grid).with_rng(seeded_rng(1)).random_solution(&[0; 625]).unwrap();
+        assert!(grid.all_regions().iter().all(|region| {
+            let mut digits: Vec<usize> = region.iter().map(|cell| solution[cell]).collect();
+            digits.sort();
+            digits == (1..26).collect::<Vec<_>>()
+        }));
+    }
 }
@@ -0,0 +1,142 @@
+//! Generate puzzles on several threads at once.
+
+use analyser::{rate_if_meets_criteria, Criteria, Rating};
+use generator::brute_force::Backend;
+use generator::patterns::PatternPuzzlesIterator;
+use generator::symmetry::{Symmetry, SymmetricPuzzlesIterator};
+use grid::{Grid, GridSize};
+use grid::variants::grid_from_empty_grid_and_clues;
+
+use rand::{Rng, SeedableRng, StdRng};
+
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver};
+use std::thread;
+use std::thread::JoinHandle;
+
+/// An iterator over puzzles produced by several worker threads. Each worker runs its own search
+/// from a random seed drawn from the given random number generator. When searching around a
+/// pattern, the workers share a single set of the puzzles seen so far so that no puzzle is
+/// produced twice. Each puzzle comes with its rating under the strategies of the criteria, which
+/// the worker has already worked out while checking the criteria.
+///
+/// The workers start when the first puzzle is asked for, so that the backend can still be chosen
+/// before then. When the iterator is dropped, the workers are told to stop and are waited on. Each
+/// checks whether it has been stopped before every attempt at finding a puzzle.
+pub struct ParallelPuzzlesIterator {
+    launch: Option<Box<dyn FnOnce(Backend) -> Workers + Send>>,
+    backend: Backend,
+    receiver: Option<Receiver<(Vec<usize>, Option<Rating>)>>,
+    stopped: Arc<AtomicBool>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+/// The channel that running workers send their puzzles down, and the workers themselves.
+type Workers = (Receiver<(Vec<usize>, Option<Rating>)>, Vec<JoinHandle<()>>);
+
+impl ParallelPuzzlesIterator {
+
+    /// Generate puzzles on the given empty grid with the given pattern, keeping only those which
+    /// meet the given criteria.
+    pub fn for_empty_grid_and_pattern<T: GridSize + 'static, R: Rng>(grid: Grid<T>, pattern: Vec<usize>, criteria: Criteria<'static, T>, num_threads: usize, rng: R) -> ParallelPuzzlesIterator {
+        let seen_puzzles = Arc::new(Mutex::new(HashSet::new()));
+        let empty_grid = grid.clone();
+        ParallelPuzzlesIterator::start(grid, criteria, num_threads, rng, move |worker_rng, backend| {
+            PatternPuzzlesIterator::for_empty_grid_and_pattern(empty_grid.clone(), pattern.clone(), worker_rng).with_backend(backend).with_seen_puzzles(seen_puzzles.clone())
+        })
+    }
+
+    /// Generate puzzles with the given starting grid and pattern, keeping only those which meet
+    /// the given criteria.
+    pub fn for_starting_grid_and_pattern<T: GridSize + 'static, R: Rng>(grid: Grid<T>, pattern: Vec<usize>, criteria: Criteria<'static, T>, num_threads: usize, rng: R) -> ParallelPuzzlesIterator {
+        let seen_puzzles = Arc::new(Mutex::new(HashSet::new()));
+        let starting_grid = grid.clone();
+        ParallelPuzzlesIterator::start(grid, criteria, num_threads, rng, move |worker_rng, backend| {
+            PatternPuzzlesIterator::for_starting_grid_and_pattern(starting_grid.clone(), pattern.clone(), worker_rng).with_backend(backend).with_seen_puzzles(seen_puzzles.clone())
+        })
+    }
+
+    /// Generate puzzles on the given empty grid by removing clues with the given symmetry, keeping
+    /// only those which meet the given criteria.
+    pub fn for_empty_grid_with_symmetry<T: GridSize + 'static, R: Rng>(grid: Grid<T>, symmetry: Symmetry, minimal: bool, criteria: Criteria<'static, T>, num_threads: usize, rng: R) -> ParallelPuzzlesIterator {
+        let empty_grid = grid.clone();
+        ParallelPuzzlesIterator::start(grid, criteria, num_threads, rng, move |worker_rng, backend| {
+            SymmetricPuzzlesIterator::for_empty_grid(empty_grid.clone(), symmetry, worker_rng).with_backend(backend).with_minimality(minimal)
+        })
+    }
+
+    /// Have the workers check uniqueness with the given brute-force backend rather than the
+    /// bitmask solver. This has no effect once the workers have started.
+    pub fn with_backend(mut self, backend: Backend) -> ParallelPuzzlesIterator {
+        self.backend = backend;
+        self
+    }
+
+    /// Get ready to start the workers, each of which searches with its own iterator made by
+    /// `puzzles`. Their seeds are drawn straight away.
+    fn start<T, R, I, F>(grid: Grid<T>, criteria: Criteria<'static, T>, num_threads: usize, mut rng: R, puzzles: F) -> ParallelPuzzlesIterator
+        where T: GridSize + 'static, R: Rng, I: Iterator<Item = Vec<usize>>, F: Fn(StdRng, Backend) -> I + Send + Sync + 'static {
+
+        // The channel holds at most one puzzle per worker, so that the workers don't run far ahead
+        // of the puzzles actually taken from the iterator.
+        let num_threads = num_threads.max(1);
+        let worker_rngs: Vec<StdRng> = (0..num_threads).map(|_| StdRng::from_rng(&mut rng).unwrap()).collect();
+        let stopped = Arc::new(AtomicBool::new(false));
+        let worker_stopped = stopped.clone();
+
+        let launch = move |backend: Backend| {
+            let (sender, receiver) = sync_channel(num_threads);
+            let criteria = Arc::new(criteria);
+            let puzzles = Arc::new(puzzles);
+            let workers = worker_rngs.into_iter().map(|worker_rng| {
+                let (grid, criteria, puzzles) = (grid.clone(), criteria.clone(), puzzles.clone());
+                let (sender, stopped) = (sender.clone(), worker_stopped.clone());
+                thread::spawn(move || {
+                    let mut puzzles = puzzles(worker_rng, backend);
+                    while !stopped.load(Ordering::Relaxed) {
+                        let clues = match puzzles.next() {
+                            Some(clues) => clues,
+                            None => break,
+                        };
+                        let rating = grid_from_empty_grid_and_clues(&grid, &clues).ok().and_then(|puzzle| rate_if_meets_criteria(&puzzle, &criteria));
+                        if let Some(rating) = rating {
+                            if sender.send((clues, rating)).is_err() {
+                                break;
+                            }
+                        }
+                    }
+                })
+            }).collect();
+            (receiver, workers)
+        };
+
+        ParallelPuzzlesIterator { launch: Some(Box::new(launch)), backend: Backend::Bitmask, receiver: None, stopped, workers: Vec::new() }
+    }
+}
+
+impl Iterator for ParallelPuzzlesIterator {
+
+    type Item = (Vec<usize>, Option<Rating>);
+
+    fn next(&mut self) -> Option<(Vec<usize>, Option<Rating>)> {
+        if let Some(launch) = self.launch.take() {
+            let (receiver, workers) = launch(self.backend);
+            self.receiver = Some(receiver);
+            self.workers = workers;
+        }
+        self.receiver.as_ref().and_then(|receiver| receiver.recv().ok())
+    }
+}
+
+impl Drop for ParallelPuzzlesIterator {
+    fn drop(&mut self) {
+        // Dropping the receiver wakes any workers waiting to send a puzzle.
+        self.stopped.store(true, Ordering::Relaxed);
+        self.receiver = None;
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
@@ -2,10 +2,11 @@
 
 use itertools::Itertools;
 use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
 use rand::prelude::*;
 
-use generator::brute_force::BruteForceSolver;
-use generator::canonicalization::minlex;
+use generator::brute_force::{Backend, BruteForce};
+use generator::canonicalization::Canonicalizer;
 use grid::{Grid, GridSize};
 
 /// A convenience type to represent a pattern of clues within a grid.
@@ -20,11 +21,16 @@ pub struct PatternPuzzlesIterator<T: GridSize> {
     /// The starting grid for which puzzles are being generated.
     starting_grid: Grid<T>,
 
-    /// Whether to canonicalize the generated puzzles.
-    canonicalize: bool,
+    /// The canonicalizer used to recognise puzzles equivalent to ones already seen, if any.
+    canonicalizer: Option<Canonicalizer<T>>,
 
     /// A brute-force solver configured to work with this grid
-    brute_force_solver: BruteForceSolver,
+    brute_force_solver: Box<dyn BruteForce>,
+
+    /// The backend of the brute-force solver, and whether it ignores the candidates of the
+    /// starting grid as it is for an empty grid.
+    backend: Backend,
+    empty: bool,
 
     /// The stack of seed puzzles still to be examined.
     seed_stack: Vec<Puzzle>,
@@ -32,66 +38,151 @@ pub struct PatternPuzzlesIterator<T: GridSize> {
     /// The queue of puzzles waiting to be returned from the iterator.
     iteration_queue: Vec<Puzzle>,
 
-    /// A set of puzzles already seen.
-    seen_puzzles: HashSet<Puzzle>,
+    /// A set of puzzles already seen, which may be shared with other iterators.
+    seen_puzzles: Arc<Mutex<HashSet<Puzzle>>>,
 
     /// The pattern that the clues of generated puzzles will occur in.
     pattern: Pattern,
+
+    /// The source of randomness for seeds and for the order of the search.
+    rng: StdRng,
 }
 
 impl <T: GridSize> PatternPuzzlesIterator<T> {
 
-    /// An iterator over puzzles on the given empty grid using a random seed.
-    pub fn for_empty_grid_and_pattern(starting_grid: Grid<T>, pattern: Pattern) -> PatternPuzzlesIterator<T> {
+    /// An iterator over puzzles on the given empty grid using a random seed, drawn from the given
+    /// random number generator.
+    pub fn for_empty_grid_and_pattern<R: Rng>(starting_grid: Grid<T>, pattern: Pattern, mut rng: R) -> PatternPuzzlesIterator<T> {
+        let mut rng = StdRng::from_rng(&mut rng).unwrap();
         loop {
-            if let Some(puzzle) = PatternPuzzlesIterator::random_seed(&starting_grid, &pattern) {
-                let brute_force_solver = BruteForceSolver::for_empty_grid(&starting_grid);
+            if let Some(puzzle) = PatternPuzzlesIterator::random_seed(&starting_grid, &pattern, &mut rng) {
+                let brute_force_solver = Backend::Bitmask.for_empty_grid(&starting_grid, &mut rng);
+                // Grids too large to canonicalize fall back on recognising puzzles by their clues.
+                let canonicalizer = Canonicalizer::for_grid(&starting_grid);
                 return PatternPuzzlesIterator {
                     starting_grid: starting_grid,
-                    canonicalize: true,
+                    canonicalizer: canonicalizer.ok(),
                     brute_force_solver: brute_force_solver,
+                    backend: Backend::Bitmask,
+                    empty: true,
                     seed_stack: vec![puzzle],
                     iteration_queue: vec![],
-                    seen_puzzles: HashSet::new(),
+                    seen_puzzles: Arc::new(Mutex::new(HashSet::new())),
                     pattern: pattern,
+                    rng: rng,
                 }
             }
         }
     }
 
-    /// An iterator over puzzles with the given starting grid using a random seed.
-    pub fn for_starting_grid_and_pattern(starting_grid: Grid<T>, pattern: Pattern) -> PatternPuzzlesIterator<T> {
+    /// An iterator over puzzles with the given starting grid using a random seed, drawn from the
+    /// given random number generator.
+    pub fn for_starting_grid_and_pattern<R: Rng>(starting_grid: Grid<T>, pattern: Pattern, mut rng: R) -> PatternPuzzlesIterator<T> {
+        let mut rng = StdRng::from_rng(&mut rng).unwrap();
         loop {
-            if let Some(puzzle) = PatternPuzzlesIterator::random_seed(&starting_grid, &pattern) {
-                let brute_force_solver = BruteForceSolver::for_starting_grid(&starting_grid);
+            if let Some(puzzle) = PatternPuzzlesIterator::random_seed(&starting_grid, &pattern, &mut rng) {
+                let brute_force_solver = Backend::Bitmask.for_starting_grid(&starting_grid, &mut rng);
                 return PatternPuzzlesIterator {
                     starting_grid: starting_grid,
-                    canonicalize: false,
+                    canonicalizer: None,
                     brute_force_solver: brute_force_solver,
+                    backend: Backend::Bitmask,
+                    empty: false,
                     seed_stack: vec![puzzle],
                     iteration_queue: vec![],
-                    seen_puzzles: HashSet::new(),
+                    seen_puzzles: Arc::new(Mutex::new(HashSet::new())),
                     pattern: pattern,
+                    rng: rng,
+                }
+            }
+        }
+    }
+
+    /// Check uniqueness with the given brute-force backend rather than the bitmask solver.
+    pub fn with_backend(mut self, backend: Backend) -> PatternPuzzlesIterator<T> {
+        if backend != self.backend {
+            self.brute_force_solver = if self.empty {
+                backend.for_empty_grid(&self.starting_grid, &mut self.rng)
+            } else {
+                backend.for_starting_grid(&self.starting_grid, &mut self.rng)
+            };
+            self.backend = backend;
+        }
+        self
+    }
+
+    /// Share the set of puzzles already seen with other iterators, so that no puzzle is produced by
+    /// more than one of them.
+    pub fn with_seen_puzzles(mut self, seen_puzzles: Arc<Mutex<HashSet<Puzzle>>>) -> PatternPuzzlesIterator<T> {
+        self.seen_puzzles = seen_puzzles;
+        self
+    }
+
+    /// Perform a +2/-2 vicinity search on the given puzzle, finding all of the puzzles which have a
+    /// unique solution, differ from it in at most two clues and aren't equivalent to any puzzle
+    /// seen so far. Each puzzle is paired with the canonical form used to recognise it.
+    pub fn vicinity(&mut self, current_puzzle: &Puzzle) -> Vec<(Puzzle, Puzzle)> {
+
+        let mut next_puzzles = Vec::new();
+        for (&clue1, &clue2) in self.pattern.iter().tuple_combinations() {
+
+            // Set the two clues that will be modified to 0
+            let mut puzzle = current_puzzle.clone();
+            puzzle[clue1] = 0; puzzle[clue2] = 0;
+
+            // Find the possibilities for each of the 2 modified clues.
+            let (poss1, poss2) = (Self::valid_clues(&self.starting_grid, &puzzle, clue1), Self::valid_clues(&self.starting_grid, &puzzle, clue2));
+
+            // Find all of the puzzles that are within the vicinity of the original puzzle.
+            for &c1 in &poss1 {
+                puzzle[clue1] = c1;
+                for &c2 in &poss2 {
+                    puzzle[clue2] = c2;
+
+                    // Check if the puzzle has a unique solution, and only then find its canonical
+                    // form as that takes longer.
+                    if self.brute_force_solver.has_unique_solution(&puzzle) {
+                        let canonical_puzzle = match self.canonicalizer {
+                            Some(ref canonicalizer) => canonicalizer.canonicalize(&puzzle).0,
+                            None => puzzle.clone(),
+                        };
+                        if !self.seen_puzzles.lock().unwrap().contains(&canonical_puzzle) {
+                            next_puzzles.push((puzzle.clone(), canonical_puzzle));
+                        }
+                    }
                 }
             }
         }
+
+        // Shuffle all of the next puzzles so that the search does not excessively focus on changing
+        // the same clues over and over again.
+        next_puzzles.sort_by(|(_, canonical1), (_, canonical2)| canonical1.cmp(canonical2));
+        next_puzzles.dedup_by(|(_, canonical1), (_, canonical2)| canonical1 == canonical2);
+        self.rng.shuffle(&mut next_puzzles);
+        next_puzzles
+    }
+
+    /// Record that the puzzle with the given canonical form has been seen, returning false if it
+    /// had already been seen - possibly by another iterator sharing the same set.
+    pub fn mark_seen(&self, canonical_puzzle: &Puzzle) -> bool {
+        self.seen_puzzles.lock().unwrap().insert(canonical_puzzle.clone())
     }
 
     /// Produce a random seed puzzle - possibly without a unique solution - that can be used as the
     /// starting point for a search.
-    fn random_seed(starting_grid: &Grid<T>, pattern: &Pattern) -> Option<Puzzle> {
+    fn random_seed(starting_grid: &Grid<T>, pattern: &Pattern, rng: &mut StdRng) -> Option<Puzzle> {
         let mut puzzle = starting_grid.cells().map(|c| starting_grid.value(c).unwrap_or(0));
         for &cell in pattern {
             let valid_clues = PatternPuzzlesIterator::valid_clues(starting_grid, &puzzle, cell);
             if valid_clues.is_empty() { return None; }
-            else { puzzle[cell] = *thread_rng().choose(&valid_clues).unwrap(); }
+            else { puzzle[cell] = *rng.choose(&valid_clues).unwrap(); }
         }
         Some(puzzle)
     }
 
     /// Find the clues are are valid in the given position, from the current puzzle state.
     fn valid_clues(starting_grid: &Grid<T>, puzzle: &Puzzle, cell: usize) -> Vec<usize> {
-        let mut valid = vec![false; 10];
+        let mut valid = vec![false; T::size() + 1];
         for candidate in starting_grid.candidates(cell).iter() {
             valid[candidate] = true;
         }
@@ -123,7 +214,7 @@ impl <T: GridSize> Iterator for PatternPuzzlesIterator<T> {
             // If the stack has been exhausted, then we are finished.
             if self.seed_stack.is_empty() {
                 loop {
-                    if let Some(seed) = Self::random_seed(&self.starting_grid, &self.pattern) {
+                    if let Some(seed) = Self::random_seed(&self.starting_grid, &self.pattern, &mut self.rng) {
                         self.seed_stack.push(seed);
                         break;
                     }
@@ -133,36 +224,11 @@ impl <T: GridSize> Iterator for PatternPuzzlesIterator<T> {
             // Find the puzzle at the top of the stack.
             let current_puzzle = self.seed_stack.pop().unwrap();
 
-            // Perform a +2/-2 vicinity search on this puzzle.
-            let mut next_puzzles = Vec::new();
-            for (&clue1, &clue2) in self.pattern.iter().tuple_combinations() {
-
-                // Set the two clues that will be modified to 0
-                let mut puzzle = current_puzzle.clone();
-                puzzle[clue1] = 0; puzzle[clue2] = 0;
-
-                // Find the possibilities for each of the 2 modified clues.
-                let (poss1, poss2) = (Self::valid_clues(&self.starting_grid, &puzzle, clue1), Self::valid_clues(&self.starting_grid, &puzzle, clue2));
-
-                // Find all of the puzzles that are within the vicinity of the original puzzle.
-                for &c1 in &poss1 {
-                    puzzle[clue1] = c1;
-                    for &c2 in &poss2 {
-                        puzzle[clue2] = c2;
-                        
-                        // Check if the puzzle has a unique solution
-                        let canonical_puzzle = if self.canonicalize { minlex::<T>(&puzzle) } else { puzzle.clone() };
-                        if !self.seen_puzzles.contains(&canonical_puzzle) && self.brute_force_solver.has_unique_solution(&canonical_puzzle) {
-                            self.seen_puzzles.insert(canonical_puzzle.clone());
-                            next_puzzles.push(canonical_puzzle);
-                        }
-                    }
-                }
-            }
-
-            // Shuffle all of the next puzzles so that the search does not excessively focus on changing
-            // the same clues over and over again.
-            thread_rng().shuffle(&mut next_puzzles);
+            // Move on to the unseen puzzles in the vicinity of this one.
+            let mut next_puzzles: Vec<Puzzle> = self.vicinity(&current_puzzle).into_iter()
+                .filter(|(_, canonical_puzzle)| self.mark_seen(canonical_puzzle))
+                .map(|(puzzle, _)| puzzle)
+                .collect();
             self.iteration_queue.append(&mut next_puzzles);
 
             // If any puzzles have been produced, then return the next one.
@@ -0,0 +1,158 @@
+//! Processes to generate Sudoku puzzles by removing clues from a random solution, keeping the
+//! remaining clues symmetric.
+
+use rand::prelude::*;
+
+use generator::brute_force::{Backend, BruteForce};
+use grid::{Grid, GridSize};
+
+/// A convenience type to represent a puzzle.
+type Puzzle = Vec<usize>;
+
+/// The symmetries that the clues of a generated puzzle can have.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Symmetry {
+    /// The clues can be anywhere.
+    None,
+    /// The clues are unchanged by a half turn of the grid.
+    Rotational,
+    /// The clues are unchanged by reflecting the grid from left to right.
+    Mirror,
+    /// The clues are unchanged by reflecting the grid in its main diagonal.
+    Diagonal,
+}
+
+impl Symmetry {
+
+    /// The cells which must be given or removed together with the given cell, including the cell
+    /// itself.
+    pub fn orbit<T: GridSize>(self, cell: usize) -> Vec<usize> {
+        let (size, row, column) = (T::size(), cell / T::size(), cell % T::size());
+        let image = match self {
+            Symmetry::None => cell,
+            Symmetry::Rotational => (size - 1 - row) * size + (size - 1 - column),
+            Symmetry::Mirror => row * size + (size - 1 - column),
+            Symmetry::Diagonal => column * size + row,
+        };
+        if image == cell { vec![cell] } else { vec![cell, image] }
+    }
+
+    /// Split the cells of a grid up into orbits, each listed once.
+    pub fn orbits<T: GridSize>(self) -> Vec<Vec<usize>> {
+        (0..T::size() * T::size())
+            .map(|cell| self.orbit::<T>(cell))
+            .filter(|orbit| orbit.iter().all(|&cell| cell >= orbit[0]))
+            .collect()
+    }
+}
+
+/// A structure which produces puzzles by filling in a random solution and then removing as many
+/// clues as possible while keeping the solution unique.
+pub struct SymmetricPuzzlesIterator<T: GridSize> {
+
+    /// The empty grid for which puzzles are being generated.
+    empty_grid: Grid<T>,
+
+    /// A brute-force solver configured to work with this grid, and its backend.
+    brute_force_solver: Box<dyn BruteForce>,
+    backend: Backend,
+
+    /// The symmetry that the clues of generated puzzles must have.
+    symmetry: Symmetry,
+
+    /// Whether to only produce puzzles from which no single clue can be removed.
+    minimal: bool,
+
+    /// The source of randomness for the solutions and the order in which clues are removed.
+    rng: StdRng,
+}
+
+impl <T: GridSize> SymmetricPuzzlesIterator<T> {
+
+    /// An iterator over puzzles on the given empty grid with the given symmetry, using the given
+    /// random number generator.
+    pub fn for_empty_grid<R: Rng>(empty_grid: Grid<T>, symmetry: Symmetry, mut rng: R) -> SymmetricPuzzlesIterator<T> {
+        let mut rng = StdRng::from_rng(&mut rng).unwrap();
+        let brute_force_solver = Backend::Bitmask.for_empty_grid(&empty_grid, &mut rng);
+        SymmetricPuzzlesIterator {
+            empty_grid,
+            brute_force_solver,
+            backend: Backend::Bitmask,
+            symmetry,
+            minimal: false,
+            rng,
+        }
+    }
+
+    /// Check uniqueness with the given brute-force backend rather than the bitmask solver.
+    pub fn with_backend(mut self, backend: Backend) -> SymmetricPuzzlesIterator<T> {
+        if backend != self.backend {
+            self.brute_force_solver = backend.for_empty_grid(&self.empty_grid, &mut self.rng);
+            self.backend = backend;
+        }
+        self
+    }
+
+    /// Only produce minimal puzzles, from which no clue can be removed without losing uniqueness.
+    /// Symmetric clue removal can leave single clues that aren't needed, so such puzzles are
+    /// thrown away.
+    pub fn with_minimality(mut self, minimal: bool) -> SymmetricPuzzlesIterator<T> {
+        self.minimal = minimal;
+        self
+    }
+
+    /// Produce a puzzle from a new random solution, removing each orbit of clues in a random order
+    /// unless doing so would lose uniqueness.
+    fn random_puzzle(&mut self) -> Puzzle {
+
+        let empty_puzzle = vec![0; T::size() * T::size()];
+        let solution = self.brute_force_solver.random_solution(&empty_puzzle).unwrap();
+        remove_clues(&mut *self.brute_force_solver, solution, self.symmetry.orbits::<T>(), &mut self.rng)
+    }
+
+    /// Check whether any single clue of the puzzle could be removed.
+    fn is_minimal(&mut self, puzzle: &Puzzle) -> bool {
+        let mut reduced = puzzle.clone();
+        for cell in self.empty_grid.cells().iter().filter(|&cell| puzzle[cell] != 0) {
+            reduced[cell] = 0;
+            if self.brute_force_solver.has_unique_solution(&reduced) {
+                return false;
+            }
+            reduced[cell] = puzzle[cell];
+        }
+        true
+    }
+}
+
+/// Remove each orbit of clues from the given puzzle in a random order, unless doing so would lose
+/// uniqueness according to the given solver.
+pub fn remove_clues<R: Rng + ?Sized>(brute_force_solver: &mut dyn BruteForce, mut puzzle: Puzzle, mut orbits: Vec<Vec<usize>>, rng: &mut R) -> Puzzle {
+
+    rng.shuffle(&mut orbits);
+
+    // Removing clues never makes a puzzle unique again, so an orbit which is needed now will
+    // still be needed once other clues have gone, and a single pass is enough.
+    for orbit in orbits {
+        let removed: Vec<usize> = orbit.iter().map(|&cell| puzzle[cell]).collect();
+        for &cell in &orbit { puzzle[cell] = 0; }
+        if !brute_force_solver.has_unique_solution(&puzzle) {
+            for (&cell, &clue) in orbit.iter().zip(removed.iter()) { puzzle[cell] = clue; }
+        }
+    }
+
+    puzzle
+}
+
+impl <T: GridSize> Iterator for SymmetricPuzzlesIterator<T> {
+
+    type Item = Puzzle;
+
+    fn next(&mut self) -> Option<Puzzle> {
+        loop {
+            let puzzle = self.random_puzzle();
+            if !self.minimal || self.symmetry == Symmetry::None || self.is_minimal(&puzzle) {
+                return Some(puzzle);
+            }
+        }
+    }
+}
@@ -0,0 +1,158 @@
+//! Processes to generate Sudoku puzzles which aim for a particular difficulty, by hill-climbing
+//! through the vicinity of puzzles with a specific pattern of clues.
+
+use rand::Rng;
+
+use analyser::{meets_criteria, rate, Criteria};
+use generator::brute_force::Backend;
+use generator::patterns::PatternPuzzlesIterator;
+use grid::{Grid, GridSize};
+use grid::variants::grid_from_empty_grid_and_clues;
+use solver::SolveConfiguration;
+
+/// A convenience type to represent a pattern of clues within a grid.
+type Pattern = Vec<usize>;
+
+/// A convenience type to represent a puzzle.
+type Puzzle = Vec<usize>;
+
+/// The number of moves the search can make without getting closer to the target before it starts
+/// again from a new puzzle.
+const MAX_MOVES_WITHOUT_IMPROVEMENT: usize = 20;
+
+/// The difficulty that a targeted search aims for.
+pub enum DifficultyTarget<'a, T: GridSize> {
+    /// Puzzles which meet the given criteria.
+    Criteria(Criteria<'a, T>),
+    /// Puzzles which can be solved with the given configuration, with a rating score between the
+    /// given minimum and maximum inclusive.
+    RatingBand(SolveConfiguration, usize, usize),
+}
+
+impl <'a, T: GridSize> DifficultyTarget<'a, T> {
+
+    /// How far the given puzzle is from the target, where a distance of 0 means the target has
+    /// been met. Criteria are either met or not, while a rating band is missed by the number of
+    /// strategies between the rating and the band, counting an unsolvable puzzle as harder than
+    /// every strategy.
+    pub fn distance(&self, grid: &Grid<T>) -> usize {
+        match *self {
+            DifficultyTarget::Criteria(ref criteria) => if meets_criteria(grid, criteria) { 0 } else { 1 },
+            DifficultyTarget::RatingBand(ref configuration, min, max) => {
+                let score = rate(grid, configuration).map_or(configuration.strategies().len() + 1, |rating| rating.score);
+                if score < min { min - score } else { score.saturating_sub(max) }
+            },
+        }
+    }
+}
+
+/// A structure which searches for puzzles meeting a difficulty target. Starting from a puzzle with
+/// the given pattern, it repeatedly moves to a puzzle in its +2/-2 vicinity which is closer to
+/// the target, or failing that one which is just as close. If the search stalls then it starts
+/// again from a fresh puzzle.
+pub struct TargetedPuzzlesIterator<'a, T: GridSize> {
+
+    /// The underlying iterator, which provides fresh puzzles and the vicinity of each puzzle.
+    puzzles: PatternPuzzlesIterator<T>,
+
+    /// The starting grid for which puzzles are being generated.
+    starting_grid: Grid<T>,
+
+    /// The difficulty that the generated puzzles should have.
+    target: DifficultyTarget<'a, T>,
+
+    /// The puzzle that the search is currently at, and its distance from the target.
+    current: Option<(Puzzle, usize)>,
+
+    /// The number of moves made since the search last got closer to the target.
+    moves_without_improvement: usize,
+}
+
+impl <'a, T: GridSize> TargetedPuzzlesIterator<'a, T> {
+
+    /// An iterator over puzzles on the given empty grid which meet the target.
+    pub fn for_empty_grid_and_pattern<R: Rng>(starting_grid: Grid<T>, pattern: Pattern, target: DifficultyTarget<'a, T>, rng: R) -> TargetedPuzzlesIterator<'a, T> {
+        let puzzles = PatternPuzzlesIterator::for_empty_grid_and_pattern(starting_grid.clone(), pattern, rng);
+        TargetedPuzzlesIterator::new(puzzles, starting_grid, target)
+    }
+
+    /// An iterator over puzzles with the given starting grid which meet the target.
+    pub fn for_starting_grid_and_pattern<R: Rng>(starting_grid: Grid<T>, pattern: Pattern, target: DifficultyTarget<'a, T>, rng: R) -> TargetedPuzzlesIterator<'a, T> {
+        let puzzles = PatternPuzzlesIterator::for_starting_grid_and_pattern(starting_grid.clone(), pattern, rng);
+        TargetedPuzzlesIterator::new(puzzles, starting_grid, target)
+    }
+
+    /// Check uniqueness with the given brute-force backend rather than the bitmask solver.
+    pub fn with_backend(mut self, backend: Backend) -> TargetedPuzzlesIterator<'a, T> {
+        self.puzzles = self.puzzles.with_backend(backend);
+        self
+    }
+
+    fn new(puzzles: PatternPuzzlesIterator<T>, starting_grid: Grid<T>, target: DifficultyTarget<'a, T>) -> TargetedPuzzlesIterator<'a, T> {
+        TargetedPuzzlesIterator {
+            puzzles,
+            starting_grid,
+            target,
+            current: None,
+            moves_without_improvement: 0,
+        }
+    }
+
+    fn distance(&self, puzzle: &Puzzle) -> usize {
+        grid_from_empty_grid_and_clues(&self.starting_grid, puzzle).map_or(usize::MAX, |grid| self.target.distance(&grid))
+    }
+}
+
+impl <'a, T: GridSize> Iterator for TargetedPuzzlesIterator<'a, T> {
+
+    type Item = Puzzle;
+
+    fn next(&mut self) -> Option<Puzzle> {
+
+        loop {
+
+            // Start again from a fresh puzzle if there's nowhere to go from the current one.
+            if self.current.is_none() || self.moves_without_improvement >= MAX_MOVES_WITHOUT_IMPROVEMENT {
+                let puzzle = self.puzzles.next()?;
+                let distance = self.distance(&puzzle);
+                self.current = Some((puzzle.clone(), distance));
+                self.moves_without_improvement = 0;
+                if distance == 0 {
+                    return Some(puzzle);
+                }
+            }
+
+            // Move to the first neighbour which is closer to the target, remembering the first
+            // which is just as close in case there are none.
+            let (current_puzzle, current_distance) = self.current.take().unwrap();
+            let mut sideways = None;
+            for (puzzle, canonical_puzzle) in self.puzzles.vicinity(&current_puzzle) {
+                let distance = self.distance(&puzzle);
+                if distance == 0 || distance < current_distance {
+                    if !self.puzzles.mark_seen(&canonical_puzzle) {
+                        continue;
+                    }
+                    self.current = Some((puzzle.clone(), distance));
+                    self.moves_without_improvement = 0;
+                    if distance == 0 {
+                        return Some(puzzle);
+                    }
+                    break;
+                }
+                if distance == current_distance && sideways.is_none() {
+                    sideways = Some((puzzle, canonical_puzzle));
+                }
+            }
+
+            // Crossing a plateau doesn't count as progress, so the search can't wander forever.
+            if self.current.is_none() {
+                if let Some((puzzle, canonical_puzzle)) = sideways {
+                    if self.puzzles.mark_seen(&canonical_puzzle) {
+                        self.current = Some((puzzle, current_distance));
+                        self.moves_without_improvement += 1;
+                    }
+                }
+            }
+        }
+    }
+}
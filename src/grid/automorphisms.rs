@@ -0,0 +1,160 @@
+//! Detection of the symmetries of a grid's layout.
+
+use grid::{Grid, GridSize};
+use grid::cellset::CellSet;
+use grid::transform::{band_preserving_orders, box_shape, swapped, Transform};
+
+use std::collections::HashSet;
+
+/// The symmetries of a grid's layout - the rearrangements of cells which map its regions onto
+/// regions and its neighbours onto neighbours, so that any puzzle on the grid is turned into an
+/// equivalent puzzle on the same grid.
+///
+/// Only the rearrangements of a standard Sudoku of the same size are considered - permuting rows
+/// within bands, bands, columns within stacks and stacks, and transposing when the boxes are
+/// square.
+#[derive(Clone, Debug)]
+pub enum Automorphisms {
+    /// Every rearrangement of a standard Sudoku is a symmetry of the layout.
+    Standard,
+    /// Only the listed rearrangements are symmetries of the layout. The identity is always
+    /// included.
+    Listed(Vec<Transform>),
+}
+
+impl Automorphisms {
+
+    /// The number of symmetries.
+    pub fn count<T: GridSize>(&self) -> usize {
+        match *self {
+            Automorphisms::Standard => {
+                // Count the orderings of the bands and of the lines within each band, rather than
+                // listing them, as there are millions for larger grids.
+                let (height, width) = box_shape(T::size());
+                let factorial = |n: usize| (1..n + 1).product::<usize>();
+                let orders = |band: usize| factorial(T::size() / band) * factorial(band).pow((T::size() / band) as u32);
+                orders(height) * orders(width) * if height == width { 2 } else { 1 }
+            },
+            Automorphisms::Listed(ref transforms) => transforms.len(),
+        }
+    }
+}
+
+impl<T: GridSize> Grid<T> {
+
+    /// Find the symmetries of the layout of this grid - its regions and neighbours, ignoring any
+    /// values or candidates.
+    pub fn automorphisms(&self) -> Automorphisms {
+
+        let (size, (height, width)) = (T::size(), box_shape(T::size()));
+        let regions: HashSet<&CellSet<T>> = self.all_regions().iter().collect();
+
+        // The whole group is a symmetry exactly when each of the swaps which generate it is.
+        let by_rows = line_swaps(size, height).into_iter().map(|rows| (0..size * size).map(|cell| rows[cell / size] * size + cell % size).collect());
+        let by_columns = line_swaps(size, width).into_iter().map(|columns| (0..size * size).map(|cell| (cell / size) * size + columns[cell % size]).collect());
+        let mut generators: Vec<Vec<usize>> = by_rows.chain(by_columns).collect();
+        if height == width {
+            generators.push((0..size * size).map(|cell| (cell % size) * size + cell / size).collect());
+        }
+        if generators.iter().all(|cells| self.preserves_layout(cells, &regions)) {
+            return Automorphisms::Standard;
+        }
+
+        // Otherwise search through the rearrangements, choosing the source of each column in turn
+        // and only carrying on while each cell comes from a cell which looks alike, and cells which
+        // are neighbours come from cells which are too.
+        let invariant = |cell: usize| (self.all_regions().iter().filter(|region| region.contains(cell)).count(), self.neighbours(cell).len());
+        let invariants: Vec<_> = (0..size * size).map(invariant).collect();
+        let is_neighbour: Vec<bool> = (0..size * size * size * size).map(|pair| self.neighbours(pair / (size * size)).contains(pair % (size * size))).collect();
+
+        let mut orientations = vec![(0..size * size).collect::<Vec<usize>>()];
+        if height == width {
+            orientations.push((0..size * size).map(|cell| (cell % size) * size + cell / size).collect());
+        }
+
+        let mut automorphisms = Vec::new();
+        for orientation in &orientations {
+            for rows in band_preserving_orders(size, height) {
+                let source = |row: usize, column: usize| orientation[rows[row] * size + column];
+                let consistent = |columns: &[usize], source_column: usize| {
+                    let column = columns.len();
+                    (0..size).all(|row| invariants[row * size + column] == invariants[source(row, source_column)])
+                        && (0..size).all(|row| (0..column + 1).all(|other_column| (0..size).all(|other_row| {
+                            let other_source_column = if other_column == column { source_column } else { columns[other_column] };
+                            let (cell, other_cell) = (row * size + column, other_row * size + other_column);
+                            let (source_cell, other_source_cell) = (source(row, source_column), source(other_row, other_source_column));
+                            is_neighbour[cell * size * size + other_cell] == is_neighbour[source_cell * size * size + other_source_cell]
+                        })))
+                };
+                self.search_columns(&consistent, width, &mut Vec::new(), &mut |columns| {
+                    let cells: Vec<usize> = (0..size * size).map(|cell| source(cell / size, columns[cell % size])).collect();
+                    if self.preserves_layout(&cells, &regions) {
+                        automorphisms.push(Transform::new(cells, (0..size + 1).collect()));
+                    }
+                });
+            }
+        }
+
+        Automorphisms::Listed(automorphisms)
+    }
+
+    /// Try every choice of source column for each column which keeps the stacks together and is
+    /// consistent with the columns chosen so far, calling the visitor with each complete choice.
+    fn search_columns<C: Fn(&[usize], usize) -> bool, F: FnMut(&[usize])>(&self, consistent: &C, width: usize, columns: &mut Vec<usize>, visit: &mut F) {
+        let column = columns.len();
+        if column == T::size() {
+            visit(columns);
+            return;
+        }
+        for source_column in 0..T::size() {
+            let same_stack = if column.is_multiple_of(width) {
+                columns.iter().all(|&used| used / width != source_column / width)
+            } else {
+                source_column / width == columns[column - column % width] / width && !columns.contains(&source_column)
+            };
+            if same_stack && consistent(columns, source_column) {
+                columns.push(source_column);
+                self.search_columns(consistent, width, columns, visit);
+                columns.pop();
+            }
+        }
+    }
+
+    /// Check whether moving each cell of the grid from the given source cell maps the regions onto
+    /// regions and the neighbours onto neighbours.
+    fn preserves_layout(&self, cells: &[usize], regions: &HashSet<&CellSet<T>>) -> bool {
+        let mut destinations = vec![0; cells.len()];
+        for (destination, &source) in cells.iter().enumerate() {
+            destinations[source] = destination;
+        }
+        let image = |set: &CellSet<T>| CellSet::from_cells(set.iter().map(|cell| destinations[cell]));
+        self.all_regions().iter().all(|region| regions.contains(&image(region)))
+            && (0..cells.len()).all(|cell| image(self.neighbours(cell)) == *self.neighbours(destinations[cell]))
+    }
+}
+
+/// The orderings of lines which swap two neighbouring lines within a band, or two neighbouring
+/// bands.
+fn line_swaps(size: usize, band: usize) -> Vec<Vec<usize>> {
+    let within_bands = (0..size - 1).filter(|line| line % band < band - 1).map(|line| swapped(size, &[line], &[line + 1]));
+    let bands = (0..size / band - 1).map(|idx| {
+        let lines: Vec<usize> = (idx * band..(idx + 2) * band).collect();
+        swapped(size, &lines[..band], &lines[band..])
+    });
+    within_bands.chain(bands).collect()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use grid::variants::*;
+
+    #[test]
+    fn test_automorphism_counts() {
+        assert_eq!(empty_classic().automorphisms().count::<Grid9>(), 3359232);
+        assert_eq!(empty_six_by_six().automorphisms().count::<Grid6>(), 3456);
+        assert_eq!(empty_diagonal().automorphisms().count::<Grid9>(), 96);
+        assert_eq!(empty_windoku().automorphisms().count::<Grid9>(), 128);
+        assert_eq!(empty_antiknight().automorphisms().count::<Grid9>(), 8);
+    }
+}
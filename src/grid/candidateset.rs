@@ -40,6 +40,12 @@ impl <T: GridSize> fmt::Display for CandidateSet<T> {
     }
 }
 
+impl <T: GridSize> fmt::Debug for CandidateSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
 impl<T: GridSize> CandidateSet<T> {
 
     /// Create an empty `CandidateSet`
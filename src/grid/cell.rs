@@ -3,9 +3,12 @@
 use grid::Candidate;
 use grid::candidateset::CandidateSet;
 use grid::fixed_size::GridSize;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 /// A single cell within a Sudoku grid
 #[derive(Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(bound = ""))]
 pub struct Cell<T: GridSize> {
 
     /// The value, if any, held by this `Cell`
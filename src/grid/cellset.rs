@@ -1,54 +1,58 @@
 // ! A structure which stores sets of cells within the grid as bitmasks.
 
 use grid::CellIdx;
-use grid::fixed_size::GridSize;
+use grid::fixed_size::{CellBits, GridSize};
+use std::fmt;
 use std::marker::PhantomData;
 use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not};
 
 /// A set of cells from a Sudoku grid, represented internally as a bitmask.
-#[derive(Eq, PartialEq, Clone, Hash)]
+///
+/// The bitmask is held inline, with as many words as the size of the grid needs, so that a
+/// `CellSet` can be copied freely without allocating.
+#[derive(Eq, PartialEq, Clone, Copy, Hash)]
 pub struct CellSet<T: GridSize> {
 
     /// A bitmask representing the cells contained in this `CellSet`
-    bits: Vec<u64>,
+    bits: T::CellBits,
 
     /// Phantom data since the generic type is purely for compile-time safety
     size: PhantomData<T>,
 }
 
 /// A structure capable of iterating over the cells held in a `CellSet`.
-pub struct CellSetIterator {
+pub struct CellSetIterator<T: GridSize> {
 
     /// A bitmask representing the cells yet to be iterated over
-    bits: Vec<u64>,
+    bits: T::CellBits,
 
     /// The index of the currently-active bitmask
     active_idx: usize,
 }
 
-impl Iterator for CellSetIterator {
+impl<T: GridSize> Iterator for CellSetIterator<T> {
 
     type Item = CellIdx;
 
     fn next(&mut self) -> Option<CellIdx> {
 
-        if self.active_idx >= self.bits.len() {
-            return None;
-        }
-
-        while self.bits[self.active_idx] == 0 && self.active_idx < self.bits.len() - 1 {
+        let words = self.bits.as_mut();
+        while self.active_idx < words.len() {
+            let mask = words[self.active_idx];
+            if mask != 0 {
+                words[self.active_idx] = mask & (mask - 1);
+                return Some(mask.trailing_zeros() as CellIdx + 64 * self.active_idx);
+            }
             self.active_idx += 1;
         }
 
-        let mask = self.bits[self.active_idx];
+        None
+    }
+}
 
-        if mask != 0 {
-            let next = mask.trailing_zeros() as CellIdx;
-            self.bits[self.active_idx] = mask & (mask - 1);
-            Some(next + 64 * self.active_idx)
-        } else {
-            None
-        }
+impl<T: GridSize> fmt::Debug for CellSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
     }
 }
 
@@ -57,7 +61,7 @@ impl<T: GridSize> CellSet<T> {
     /// Create an empty `CellSet`
     pub fn empty() -> CellSet<T> {
         CellSet {
-            bits: vec![0; (T::size() * T::size() + 64 - 1) / 64],
+            bits: T::CellBits::zero(),
             size: PhantomData,
         }
     }
@@ -70,7 +74,7 @@ impl<T: GridSize> CellSet<T> {
     /// Create a new `CellSet` containing only the given single cell
     pub fn from_cell(cell: CellIdx) -> CellSet<T> {
         let mut cell_set = CellSet::empty();
-        cell_set.bits[cell / 64] = 1 << (cell % 64);
+        cell_set.add_cell(cell);
         cell_set
     }
 
@@ -78,37 +82,37 @@ impl<T: GridSize> CellSet<T> {
     pub fn from_cells<I: IntoIterator<Item = CellIdx>>(cells: I) -> CellSet<T> {
         let mut cell_set = CellSet::empty();
         for cell in cells {
-            cell_set.bits[cell / 64] |= 1 << (cell % 64);
+            cell_set.add_cell(cell);
         }
         cell_set
     }
 
     /// Add the given cell to this `CellSet`
     pub fn add_cell(&mut self, cell: CellIdx) {
-        self.bits[cell / 64] |= 1 << (cell % 64);
+        self.bits.as_mut()[cell / 64] |= 1 << (cell % 64);
     }
 
     /// Remove the given cell from this `CellSet`
     pub fn remove_cell(&mut self, cell: CellIdx) {
-        self.bits[cell / 64] &= !(1 << (cell % 64));
+        self.bits.as_mut()[cell / 64] &= !(1 << (cell % 64));
     }
 
     /// An iterator over the cells held in this `CellSet`
-    pub fn iter(&self) -> CellSetIterator {
+    pub fn iter(&self) -> CellSetIterator<T> {
         CellSetIterator {
-            bits: self.bits.clone(),
+            bits: self.bits,
             active_idx: 0,
         }
     }
 
     /// The number of cells contained in this `CellSet`
     pub fn len(&self) -> usize {
-        self.bits.iter().map(|b| b.count_ones() as usize).sum()
+        self.bits.as_ref().iter().map(|b| b.count_ones() as usize).sum()
     }
 
     /// Check if this `CellSet` is empty
     pub fn is_empty(&self) -> bool {
-        self.len() == 0
+        self.bits.as_ref().iter().all(|&b| b == 0)
     }
 
     /// Get the first cell from this `CellSet`
@@ -118,13 +122,13 @@ impl<T: GridSize> CellSet<T> {
 
     /// Determine whether this `CellSet` contains a particular cell or not
     pub fn contains(&self, cell: CellIdx) -> bool {
-        self.bits[cell / 64] & (1 << (cell % 64)) != 0
+        self.bits.as_ref()[cell / 64] & (1 << (cell % 64)) != 0
     }
 
     /// Determine whether this `CellSet` contains another `CellSet` as a subset
     pub fn contains_all(&self, other: &CellSet<T>) -> bool {
-        self.bits.iter()
-            .zip(other.bits.iter())
+        self.bits.as_ref().iter()
+            .zip(other.bits.as_ref().iter())
             .all(|(&own_bits, &other_bits)| other_bits & own_bits == other_bits)
     }
 
@@ -148,10 +152,26 @@ impl<T: GridSize> CellSet<T> {
         self.iter().map(f).collect()
     }
 
+    /// Combine the words of the bitmasks of two `CellSet`s with the given operation
+    fn combine<F: Fn(u64, u64) -> u64>(mut self, other: &CellSet<T>, op: F) -> CellSet<T> {
+        for (own_bits, &other_bits) in self.bits.as_mut().iter_mut().zip(other.bits.as_ref().iter()) {
+            *own_bits = op(*own_bits, other_bits);
+        }
+        self
+    }
 }
 
-macro_rules! binop_from_ref_ref {
-    ($t: ident, $f: ident) => {
+macro_rules! binop {
+    ($t: ident, $f: ident, $assign_t: ident, $assign_f: ident, $op: tt) => {
+
+        impl<'a, 'b, T: GridSize> $t<&'a CellSet<T>> for &'b CellSet<T> {
+
+            type Output = CellSet<T>;
+
+            fn $f(self, rhs: &'a CellSet<T>) -> CellSet<T> {
+                self.combine(rhs, |own_bits, other_bits| own_bits $op other_bits)
+            }
+        }
 
         impl<'a, T: GridSize> $t<&'a CellSet<T>> for CellSet<T> {
 
@@ -179,119 +199,24 @@ macro_rules! binop_from_ref_ref {
                 $t::$f(&self, &rhs)
             }
         }
-    }
-}
-
-// `BitAnd` implementation for `CellSet`
-impl<'a, 'b, T: GridSize> BitAnd<&'a CellSet<T>> for &'b CellSet<T> {
-
-    type Output = CellSet<T>;
-
-    fn bitand(self, rhs: &'a CellSet<T>) -> CellSet<T> {
-
-        let new_bits = self.bits.iter()
-            .zip(rhs.bits.iter())
-            .map(|(own_bits, other_bits)| own_bits & other_bits)
-            .collect();
-
-        CellSet {
-            bits: new_bits,
-            size: PhantomData,
-        }
-    }
-}
-
-impl<'a, T: GridSize> BitAndAssign<&'a CellSet<T>> for CellSet<T> {
-    fn bitand_assign(&mut self, other: &'a CellSet<T>) {
-        for (idx, mask) in other.bits.iter().enumerate() {
-            self.bits[idx] &= mask;
-        }
-    }
-}
-
-impl<T: GridSize> BitAndAssign<CellSet<T>> for CellSet<T> {
-    fn bitand_assign(&mut self, other: CellSet<T>) {
-        for (idx, mask) in other.bits.iter().enumerate() {
-            self.bits[idx] &= mask;
-        }
-    }
-}
-
-binop_from_ref_ref!(BitAnd, bitand);
-
-// `BitOr` implementation for `CellSet`
-impl<'a, 'b, T: GridSize> BitOr<&'a CellSet<T>> for &'b CellSet<T> {
-
-    type Output = CellSet<T>;
-
-    fn bitor(self, rhs: &'a CellSet<T>) -> CellSet<T> {
-
-        let new_bits = self.bits.iter()
-            .zip(rhs.bits.iter())
-            .map(|(own_bits, other_bits)| own_bits | other_bits)
-            .collect();
-
-        CellSet {
-            bits: new_bits,
-            size: PhantomData,
-        }
-    }
-}
-
-impl<'a, T: GridSize> BitOrAssign<&'a CellSet<T>> for CellSet<T> {
-    fn bitor_assign(&mut self, other: &'a CellSet<T>) {
-        for (idx, mask) in other.bits.iter().enumerate() {
-            self.bits[idx] |= mask;
-        }
-    }
-}
-
-impl<T: GridSize> BitOrAssign<CellSet<T>> for CellSet<T> {
-    fn bitor_assign(&mut self, other: CellSet<T>) {
-        for (idx, mask) in other.bits.iter().enumerate() {
-            self.bits[idx] |= mask;
-        }
-    }
-}
-
-binop_from_ref_ref!(BitOr, bitor);
-
-// `BitXor` implementation for `CellSet`
-impl<'a, 'b, T: GridSize> BitXor<&'a CellSet<T>> for &'b CellSet<T> {
-
-    type Output = CellSet<T>;
-
-    fn bitxor(self, rhs: &'a CellSet<T>) -> CellSet<T> {
-
-        let new_bits = self.bits.iter()
-            .zip(rhs.bits.iter())
-            .map(|(own_bits, other_bits)| own_bits ^ other_bits)
-            .collect();
 
-        CellSet {
-            bits: new_bits,
-            size: PhantomData,
-        }
-    }
-}
-
-impl<'a, T: GridSize> BitXorAssign<&'a CellSet<T>> for CellSet<T> {
-    fn bitxor_assign(&mut self, other: &'a CellSet<T>) {
-        for (idx, mask) in other.bits.iter().enumerate() {
-            self.bits[idx] ^= mask;
+        impl<'a, T: GridSize> $assign_t<&'a CellSet<T>> for CellSet<T> {
+            fn $assign_f(&mut self, other: &'a CellSet<T>) {
+                *self = $t::$f(&*self, other);
+            }
         }
-    }
-}
 
-impl<T: GridSize> BitXorAssign<CellSet<T>> for CellSet<T> {
-    fn bitxor_assign(&mut self, other: CellSet<T>) {
-        for (idx, mask) in other.bits.iter().enumerate() {
-            self.bits[idx] ^= mask;
+        impl<T: GridSize> $assign_t<CellSet<T>> for CellSet<T> {
+            fn $assign_f(&mut self, other: CellSet<T>) {
+                *self = $t::$f(&*self, &other);
+            }
         }
     }
 }
 
-binop_from_ref_ref!(BitXor, bitxor);
+binop!(BitAnd, bitand, BitAndAssign, bitand_assign, &);
+binop!(BitOr, bitor, BitOrAssign, bitor_assign, |);
+binop!(BitXor, bitxor, BitXorAssign, bitxor_assign, ^);
 
 // `Not` implementation for `CellSet`
 impl<'a, T: GridSize> Not for &'a CellSet<T> {
@@ -300,32 +225,51 @@ impl<'a, T: GridSize> Not for &'a CellSet<T> {
 
     fn not(self) -> CellSet<T> {
 
-        let mut negated_bits: Vec<_> = self.bits.iter().map(|mask| !mask).collect();
+        // Only the bits for cells of the grid are set, leaving the rest of the last word clear.
+        let mut negated = *self;
         let number_of_cells = T::size() * T::size();
-        let high_order_mask = (1 << (number_of_cells % 64)) - 1;
-        negated_bits[number_of_cells / 64] &= high_order_mask;
-
-        CellSet {
-            bits: negated_bits,
-            size: PhantomData,
+        for (idx, mask) in negated.bits.as_mut().iter_mut().enumerate() {
+            let cells_in_word = number_of_cells.saturating_sub(64 * idx).min(64);
+            let valid = if cells_in_word == 64 { !0 } else { (1 << cells_in_word) - 1 };
+            *mask = !*mask & valid;
         }
+        negated
     }
 }
 
-impl<'a, T: GridSize> Not for CellSet<T> {
+impl<T: GridSize> Not for CellSet<T> {
 
     type Output = CellSet<T>;
 
     fn not(self) -> CellSet<T> {
+        !&self
+    }
+}
 
-        let mut negated_bits: Vec<_> = self.bits.iter().map(|mask| !mask).collect();
-        let number_of_cells = T::size() * T::size();
-        let high_order_mask = (1 << (number_of_cells % 64)) - 1;
-        negated_bits[number_of_cells / 64] &= high_order_mask;
+#[cfg(test)]
+mod tests {
+
+    use grid::variants::{Grid6, Grid9, Grid25};
+    use super::*;
+
+    fn check_operations<T: GridSize>() {
+        let cells = T::size() * T::size();
+        let evens = CellSet::<T>::from_cells((0..cells).filter(|cell| cell % 2 == 0));
+        let thirds = CellSet::<T>::from_cells((0..cells).filter(|cell| cell % 3 == 0));
+        assert_eq!(CellSet::<T>::full().len(), cells);
+        assert_eq!((!evens).len(), cells / 2);
+        assert_eq!((evens & thirds).iter().collect::<Vec<_>>(), (0..cells).filter(|cell| cell % 6 == 0).collect::<Vec<_>>());
+        assert_eq!(evens | thirds, CellSet::from_cells((0..cells).filter(|cell| cell % 2 == 0 || cell % 3 == 0)));
+        assert_eq!((evens ^ thirds).len(), (0..cells).filter(|cell| (cell % 2 == 0) != (cell % 3 == 0)).count());
+        assert!(evens.contains_all(&(evens & thirds)) && !evens.contains_all(&thirds));
+        assert_eq!(CellSet::<T>::from_cell(cells - 1).first(), Some(cells - 1));
+        assert!((evens & !evens).is_empty());
+    }
 
-        CellSet {
-            bits: negated_bits,
-            size: PhantomData,
-        }
+    #[test]
+    fn test_cellset_operations() {
+        check_operations::<Grid6>();
+        check_operations::<Grid9>();
+        check_operations::<Grid25>();
     }
 }
@@ -0,0 +1,189 @@
+//! Encoding grids as SAT problems in DIMACS CNF form, and reading solutions back from the models
+//! that SAT solvers find.
+//!
+//! The variable for digit `d` in cell `c` is `c * size + d`, so that the variables run from 1 to
+//! `size * size * size`. A model sets exactly one variable true for each cell.
+
+use grid::{CellIdx, Grid, GridSize};
+
+use std::fmt;
+
+/// Errors that can arise when reading a solution from a DIMACS model.
+pub enum ModelParseError {
+    Unsatisfiable,
+    BadLiteral(String),
+    VariableOutOfRange(usize),
+    NoDigit(CellIdx),
+    SeveralDigits(CellIdx),
+}
+
+impl fmt::Display for ModelParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::ModelParseError::*;
+        match *self {
+            Unsatisfiable => write!(f, "The solver found the problem to be unsatisfiable"),
+            BadLiteral(ref literal) => write!(f, "'{}' is not a literal", literal),
+            VariableOutOfRange(variable) => write!(f, "The variable {} is out of range", variable),
+            NoDigit(cell) => write!(f, "The model places no digit in the cell at position {}", cell),
+            SeveralDigits(cell) => write!(f, "The model places several digits in the cell at position {}", cell),
+        }
+    }
+}
+
+impl fmt::Debug for ModelParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self, f)
+    }
+}
+
+impl<T: GridSize> Grid<T> {
+
+    /// Write out the grid as a SAT problem in DIMACS CNF form, whose models are exactly the
+    /// solutions of the grid.
+    ///
+    /// Each cell holds one of its candidates, or its value if it has been filled in, and no digit
+    /// repeats between neighbours. Every region as large as the grid must also contain each digit,
+    /// which follows from the rest but helps solvers along.
+    pub fn to_dimacs(&self) -> String {
+
+        let (size, num_cells) = (T::size(), T::size() * T::size());
+        let variable = |cell: CellIdx, digit: usize| (cell * size + digit) as isize;
+        let mut clauses: Vec<Vec<isize>> = Vec::new();
+
+        for cell in 0..num_cells {
+            let allowed: Vec<usize> = match self.value(cell) {
+                Some(value) => vec![value],
+                None => self.candidates(cell).iter().collect(),
+            };
+            clauses.push(allowed.iter().map(|&digit| variable(cell, digit)).collect());
+            for digit in (1..size + 1).filter(|digit| !allowed.contains(digit)) {
+                clauses.push(vec![-variable(cell, digit)]);
+            }
+            for first in 1..size + 1 {
+                for second in first + 1..size + 1 {
+                    clauses.push(vec![-variable(cell, first), -variable(cell, second)]);
+                }
+            }
+        }
+
+        for cell in 0..num_cells {
+            for neighbour in self.neighbours(cell).iter().filter(|&neighbour| neighbour > cell) {
+                for digit in 1..size + 1 {
+                    clauses.push(vec![-variable(cell, digit), -variable(neighbour, digit)]);
+                }
+            }
+        }
+
+        for region in self.all_regions().iter().filter(|region| region.len() == size) {
+            for digit in 1..size + 1 {
+                clauses.push(region.iter().map(|cell| variable(cell, digit)).collect());
+            }
+        }
+
+        let mut output = format!("c Sudoku of size {}: digit d in cell c is variable c * {} + d\n", size, size);
+        output.push_str(&format!("p cnf {} {}\n", num_cells * size, clauses.len()));
+        for clause in clauses {
+            for literal in clause {
+                output.push_str(&literal.to_string());
+                output.push(' ');
+            }
+            output.push_str("0\n");
+        }
+
+        output
+    }
+}
+
+/// Read the solution of a grid of the given size from a model written out by a SAT solver for the
+/// problem given by `Grid::to_dimacs`.
+///
+/// The literals may be spread over several lines, each optionally starting with `v` as in the
+/// output of the SAT competitions. Comment lines starting with `c` and the status line starting
+/// with `s` are skipped, and a trailing `0` is ignored.
+pub fn solution_from_dimacs_model<T: GridSize>(model: &str) -> Result<Vec<usize>, ModelParseError> {
+
+    let (size, num_cells) = (T::size(), T::size() * T::size());
+    let mut solution = vec![0; num_cells];
+
+    for line in model.lines().map(|line| line.trim()) {
+        if line.starts_with('c') {
+            continue;
+        }
+        if line.starts_with('s') {
+            if line.contains("UNSAT") {
+                return Err(ModelParseError::Unsatisfiable);
+            }
+            continue;
+        }
+        let literals = if let Some(rest) = line.strip_prefix('v') { rest } else { line };
+        for literal in literals.split_whitespace() {
+            let literal: isize = literal.parse().map_err(|_| ModelParseError::BadLiteral(literal.to_string()))?;
+            if literal <= 0 {
+                continue;
+            }
+            let variable = literal as usize;
+            if variable > num_cells * size {
+                return Err(ModelParseError::VariableOutOfRange(variable));
+            }
+            let (cell, digit) = ((variable - 1) / size, (variable - 1) % size + 1);
+            if solution[cell] != 0 {
+                return Err(ModelParseError::SeveralDigits(cell));
+            }
+            solution[cell] = digit;
+        }
+    }
+
+    match solution.iter().position(|&digit| digit == 0) {
+        Some(cell) => Err(ModelParseError::NoDigit(cell)),
+        None => Ok(solution),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use generator::brute_force::BruteForceSolver;
+    use generator::BruteForce;
+    use grid::variants::*;
+    use super::*;
+
+    /// Check the solution against each clause of the CNF.
+    fn satisfies(cnf: &str, solution: &[usize], size: usize) -> bool {
+        cnf.lines().filter(|line| !line.starts_with('c') && !line.starts_with('p')).all(|clause| {
+            clause.split_whitespace().map(|literal| literal.parse::<isize>().unwrap()).take_while(|&literal| literal != 0).any(|literal| {
+                let variable = literal.unsigned_abs() - 1;
+                (solution[variable / size] == variable % size + 1) == (literal > 0)
+            })
+        })
+    }
+
+    fn check<T: GridSize>(puzzle: Grid<T>) {
+        let size = T::size();
+        let cnf = puzzle.to_dimacs();
+        let solution = BruteForceSolver::for_starting_grid(&puzzle).random_solution(&puzzle.to_clues()).unwrap();
+        assert!(satisfies(&cnf, &solution, size));
+
+        // Swapping two digits of the solution breaks the rules somewhere.
+        let mut broken = solution.clone();
+        broken.swap(0, 1);
+        assert!(!satisfies(&cnf, &broken, size));
+
+        let model: Vec<String> = (1..size * size * size + 1)
+            .map(|variable| if solution[(variable - 1) / size] == (variable - 1) % size + 1 { variable.to_string() } else { format!("-{}", variable) })
+            .collect();
+        let model = format!("s SATISFIABLE\nv {}\nv 0\n", model.join(" "));
+        assert_eq!(solution_from_dimacs_model::<T>(&model).unwrap(), solution);
+    }
+
+    #[test]
+    fn test_dimacs_encoding() {
+        check(classic_from_string(".9...4.2...1.8....6.21.....4....57..1..7.9..2..84....5.....65.8....7.6...5.3...4.".to_string()).unwrap());
+        check(antiknight_from_clues(&[0; 81]).unwrap());
+        check(odd_even_from_clues(&[0; 81], &[0, 10, 20], &[1, 11, 21]).unwrap());
+        check(empty_six_by_six());
+
+        assert!(solution_from_dimacs_model::<Grid6>("s UNSATISFIABLE\n").is_err());
+        assert!(solution_from_dimacs_model::<Grid6>("v 1 2 0\n").is_err());
+        assert!(solution_from_dimacs_model::<Grid6>("v 217 0\n").is_err());
+    }
+}
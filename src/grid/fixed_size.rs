@@ -1,22 +1,43 @@
 //! A helper struct which allows the emulation of const-generics for grids of a fixed size.
 
+use std::fmt::Debug;
 use std::hash::Hash;
 
 /// Until const-generics are available, use types implementing this trait as a stand-in for an
 /// associated const on types dependent on the size of the grid.
-pub trait GridSize: Clone + Copy + PartialEq + Eq + Hash {
+pub trait GridSize: Clone + Copy + PartialEq + Eq + Hash + Debug + Send + Sync {
+
+    /// The words of a bitmask with a bit for each cell of the grid, used by `CellSet`.
+    type CellBits: CellBits;
+
     fn size() -> usize;
 }
 
+/// The storage for a bitmask over the cells of a grid - an array of words, held inline.
+pub trait CellBits: Copy + PartialEq + Eq + Hash + Debug + Send + Sync + AsRef<[u64]> + AsMut<[u64]> {
+
+    /// A bitmask with no bits set.
+    fn zero() -> Self;
+}
+
+impl<const N: usize> CellBits for [u64; N] {
+    fn zero() -> Self {
+        [0; N]
+    }
+}
+
 /// A utility macro used to easily define a struct which implements `GridSize`
 #[macro_export]
 macro_rules! define_grid_size {
     ($s:ident, $size:expr) => {
         
-        #[derive(Clone, Copy, PartialEq, Eq, Hash)]
+        #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
         pub struct $s;
 
         impl GridSize for $s {
+
+            type CellBits = [u64; usize::div_ceil($size * $size, 64)];
+
             fn size() -> usize {
                 $size
             }
@@ -1,10 +1,16 @@
 //! A structure representing a Sudoku grid.
 
+pub mod automorphisms;
 pub mod candidateset;
 pub mod cell;
 pub mod cellset;
+pub mod dimacs;
 mod fixed_size;
+pub mod pencilmarks;
 mod regions;
+#[cfg(feature = "serde")]
+mod serialization;
+pub mod transform;
 pub mod variants;
 
 use self::candidateset::CandidateSet;
@@ -16,6 +22,8 @@ use strategies::Deduction;
 use strategies::Deduction::*;
 
 use std::fmt;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 /// A named type for indexing cells of the grid.
 pub type CellIdx = usize;
@@ -24,7 +32,8 @@ pub type CellIdx = usize;
 pub type Candidate = usize;
 
 // A simple enum for choosing between rows and columns
-#[derive(Copy, Clone, PartialEq, Eq)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum RowOrColumn {
     Row,
     Column,
@@ -32,6 +41,7 @@ pub enum RowOrColumn {
 
 /// A Sudoku grid
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(bound = "", try_from = "serialization::GridFields<T>"))]
 pub struct Grid<T: GridSize> {
 
     /// The cells of the grid, in row-major order
@@ -51,6 +61,15 @@ pub struct Grid<T: GridSize> {
 
     /// The neighbours for each cell of the grid
     neighbours: Vec<CellSet<T>>,
+
+    /// The cells which can hold each value, indexed by value - 1, kept up to date as values are
+    /// placed and eliminated
+    #[cfg_attr(feature = "serde", serde(skip))]
+    candidate_cells: Vec<CellSet<T>>,
+
+    /// The cells which have not yet been filled in
+    #[cfg_attr(feature = "serde", serde(skip))]
+    empty_cells: CellSet<T>,
 }
 
 impl <T: GridSize> fmt::Display for Grid<T> {
@@ -85,7 +104,7 @@ impl<T: GridSize> Grid<T> {
         let all_regions: Vec<_> = extra_regions.iter()
             .chain(rows.iter())
             .chain(columns.iter())
-            .map(|x| x.clone())
+            .copied()
             .collect();
 
         let neighbours = Grid::create_neighbours(&all_regions, additional_neighbours);
@@ -97,11 +116,17 @@ impl<T: GridSize> Grid<T> {
             extra_regions: extra_regions,
             all_regions: all_regions,
             neighbours: neighbours,
+            candidate_cells: vec![CellSet::full(); T::size()],
+            empty_cells: CellSet::full(),
         }
     }
 
     /// Place a value in the given cell, propagating eliminations though the grid
     pub fn place_value(&mut self, cell: CellIdx, val: Candidate) {
+        for candidate in self.candidates(cell).iter() {
+            self.candidate_cells[candidate - 1].remove_cell(cell);
+        }
+        self.empty_cells.remove_cell(cell);
         self.cells[cell].set_value(val);
         for neighbour in self.neighbours(cell).iter() {
             self.eliminate_value(neighbour, val);
@@ -111,6 +136,7 @@ impl<T: GridSize> Grid<T> {
     /// Remove a value from the cell at the given index
     pub fn eliminate_value(&mut self, cell: CellIdx, val: Candidate) {
         self.cells[cell].remove_candidate(val);
+        self.candidate_cells[val - 1].remove_cell(cell);
     }
 
     /// Apply the given deduction to the grid
@@ -137,6 +163,18 @@ impl<T: GridSize> Grid<T> {
         self.cells().iter().all(|ix| !self.is_empty(ix))
     }
 
+    /// Get the values of every cell in the grid, in row-major order, with 0 for empty cells
+    pub fn to_clues(&self) -> Vec<usize> {
+        self.cells().map(|cell| self.value(cell).unwrap_or(0))
+    }
+
+    /// Write out the values of the grid on a single line, using '.' for empty cells. On grids
+    /// larger than 9x9 the values are separated by commas.
+    pub fn to_line_string(&self) -> String {
+        let separator = if T::size() > 9 { "," } else { "" };
+        self.cells().map(|cell| self.value(cell).map_or(".".to_string(), |value| value.to_string())).join(separator)
+    }
+
     /// Get the first candidate that can go in the given cell
     pub fn first_candidate(&self, cell: CellIdx) -> Option<Candidate> {
         self.cells[cell].first_candidate()
@@ -159,14 +197,12 @@ impl<T: GridSize> Grid<T> {
 
     /// Get all of the empty cells
     pub fn empty_cells(&self) -> CellSet<T> {
-        CellSet::full().filter(|&cell| self.is_empty(cell))
+        self.empty_cells
     }
 
     /// Get the cells which are able to hold a particular value
     pub fn cells_with_candidate(&self, value: Candidate) -> CellSet<T> {
-        let cells = self.cells().iter()
-            .filter_map(|cell| if self.has_candidate(cell, value) { Some(cell) } else { None });
-        CellSet::from_cells(cells)
+        self.candidate_cells[value - 1]
     }
 
     /// Get the cells which have a particular number of candidates
@@ -181,17 +217,17 @@ impl<T: GridSize> Grid<T> {
 
     /// Determine if a particular candidate appears in the given region
     pub fn candidate_in_region(&self, candidate: Candidate, region: &CellSet<T>) -> bool {
-        region.iter().any(|cell| self.candidates(cell).has_candidate(candidate))
+        !(self.candidate_cells[candidate - 1] & region).is_empty()
     }
 
     /// Get the empty cells from the given region
     pub fn empty_cells_in_region(&self, region: &CellSet<T>) -> CellSet<T> {
-        region.filter(|&cell| self.is_empty(cell))
+        self.empty_cells & region
     }
 
     /// Get the cells in the given region which contain a particular value
     pub fn cells_with_candidate_in_region(&self, value: Candidate, region: &CellSet<T>) -> CellSet<T> {
-        region.filter(|&cell| self.has_candidate(cell, value))
+        self.candidate_cells[value - 1] & region
     }
 
     /// Get the values which appear in a given region
@@ -211,7 +247,7 @@ impl<T: GridSize> Grid<T> {
 
     /// Get all cells in the given region which contain any of the given candidates
     pub fn cells_with_candidates_in_region(&self, candidates: &CandidateSet<T>, region: &CellSet<T>) -> CellSet<T> {
-        region.filter(|&cell| candidates.iter().any(|val| self.has_candidate(cell, val)))
+        candidates.iter().fold(CellSet::empty(), |acc, val| acc | self.candidate_cells[val - 1]) & region
     }
 
     /// Get all cells in the given region with a particular number of candidates
@@ -224,6 +260,23 @@ impl<T: GridSize> Grid<T> {
         region.filter(|&cell| self.candidates(cell) == *candidates)
     }
 
+    /// Replace the given cell outright, without propagating anything to its neighbours
+    fn set_cell(&mut self, cell: CellIdx, contents: Cell<T>) {
+        for value in 1..T::size() + 1 {
+            if contents.has_candidate(value) {
+                self.candidate_cells[value - 1].add_cell(cell);
+            } else {
+                self.candidate_cells[value - 1].remove_cell(cell);
+            }
+        }
+        if contents.is_empty() {
+            self.empty_cells.add_cell(cell);
+        } else {
+            self.empty_cells.remove_cell(cell);
+        }
+        self.cells[cell] = contents;
+    }
+
     /// Determine the rows for this `Grid`
     fn create_rows() -> Vec<CellSet<T>> {
         let size = T::size();
@@ -260,3 +313,70 @@ impl<T: GridSize> Grid<T> {
         neighbours
     }
 }
+
+#[cfg(test)]
+mod tests {
+
+    use grid::transform::Transform;
+    use grid::variants::*;
+    use rand::{SeedableRng, StdRng};
+    use solver::{solve, SolveConfiguration};
+    use super::*;
+
+    /// Check the cached sets against the candidates of each cell.
+    fn check_caches<T: GridSize>(grid: &Grid<T>) {
+        for value in 1..T::size() + 1 {
+            assert_eq!(grid.cells_with_candidate(value), CellSet::full().filter(|&cell| grid.has_candidate(cell, value)));
+        }
+        assert_eq!(grid.empty_cells(), CellSet::full().filter(|&cell| grid.is_empty(cell)));
+    }
+
+    #[test]
+    fn test_candidate_caches() {
+        let puzzle = classic_from_string(".9...4.2...1.8....6.21.....4....57..1..7.9..2..84....5.....65.8....7.6...5.3...4.".to_string()).unwrap();
+        let (automorphisms, mut rng) = (puzzle.automorphisms(), StdRng::from_seed([3; 32]));
+        let mut grid = puzzle.clone();
+        check_caches(&grid);
+        for (_, deductions) in solve(&mut puzzle.clone(), &SolveConfiguration::with_all_strategies()).steps {
+            for deduction in deductions {
+                grid.apply_deduction(deduction);
+            }
+            check_caches(&grid);
+            check_caches(&Transform::random_for_automorphisms::<Grid9, _>(&automorphisms, &mut rng).apply_to_grid(&grid));
+        }
+        assert!(grid.is_solved());
+    }
+
+    #[test]
+    fn test_large_grid_line_round_trip() {
+        let mut grid = empty_sixteen_by_sixteen();
+        grid.place_value(0, 12);
+        grid.place_value(17, 1);
+        grid.place_value(255, 16);
+
+        let line = grid.to_line_string();
+        assert!(line.starts_with("12,.,"));
+        let parsed = grid_from_empty_grid_and_string(&empty_sixteen_by_sixteen(), line.clone()).unwrap();
+        assert_eq!(parsed.to_line_string(), line);
+        assert_eq!(parsed.to_clues(), grid.to_clues());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_caches_are_rebuilt_when_deserializing() {
+        use serde_json;
+
+        let mut grid = classic_from_string(".9...4.2...1.8....6.21.....4....57..1..7.9..2..84....5.....65.8....7.6...5.3...4.".to_string()).unwrap();
+        grid.eliminate_value(0, 3);
+        let json = serde_json::to_string(&grid).unwrap();
+        assert!(!json.contains("candidate_cells") && !json.contains("empty_cells"));
+
+        let parsed: Grid<Grid9> = serde_json::from_str(&json).unwrap();
+        check_caches(&parsed);
+        assert_eq!(parsed.to_pencilmark_string(), grid.to_pencilmark_string());
+
+        let mut truncated: serde_json::Value = serde_json::from_str(&json).unwrap();
+        truncated["cells"].as_array_mut().unwrap().pop();
+        assert!(serde_json::from_value::<Grid<Grid9>>(truncated).is_err());
+    }
+}
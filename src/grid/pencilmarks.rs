@@ -0,0 +1,169 @@
+//! Reading and writing grids in pencilmark form, where each cell lists its remaining candidates.
+
+use grid::{Grid, GridSize};
+use grid::transform::box_shape;
+use grid::variants::GridParseError;
+
+impl<T: GridSize> Grid<T> {
+
+    /// Write out the grid in pencilmark form. Each cell is written as the list of its candidates,
+    /// or as its value if it has been filled in, with columns aligned and boxes separated. A cell
+    /// left with a single candidate which hasn't been placed is wrapped in parentheses, so that it
+    /// can't be mistaken for a value. On grids larger than 9x9 the candidates are separated by
+    /// commas.
+    pub fn to_pencilmark_string(&self) -> String {
+
+        let size = T::size();
+        let (box_height, box_width) = box_shape(size);
+        let separator = if size > 9 { "," } else { "" };
+
+        let entries: Vec<String> = self.cells().map(|cell| match self.value(cell) {
+            Some(value) => value.to_string(),
+            None if self.num_candidates(cell) == 1 => format!("({})", self.candidates(cell).iter().next().unwrap()),
+            None => self.candidates(cell).iter().map(|c| c.to_string()).collect::<Vec<_>>().join(separator),
+        });
+
+        let widths: Vec<usize> = (0..size)
+            .map(|col| (0..size).map(|row| entries[row * size + col].len()).max().unwrap())
+            .collect();
+
+        let divider = {
+            let stacks: Vec<String> = widths.chunks(box_width)
+                .map(|stack| "-".repeat(stack.iter().map(|w| w + 2).sum::<usize>() + 1))
+                .collect();
+            format!("+{}+", stacks.join("+"))
+        };
+
+        let mut output = String::new();
+        for row in 0..size {
+            if row % box_height == 0 {
+                output.push_str(&divider);
+                output.push('\n');
+            }
+            for col in 0..size {
+                if col % box_width == 0 {
+                    output.push_str(if col == 0 { "| " } else { " | " });
+                }
+                output.push_str(&format!("{:<width$}", entries[row * size + col], width = widths[col]));
+                if (col + 1) % box_width != 0 {
+                    output.push_str("  ");
+                }
+            }
+            output.push_str(" |\n");
+        }
+        output.push_str(&divider);
+
+        output
+    }
+}
+
+/// Read a grid in pencilmark form, using the layout of the given empty grid.
+///
+/// Each cell is given as a run of digits listing its candidates, and any other characters are
+/// treated as separators. On grids larger than 9x9 the candidates are instead separated by commas.
+/// A cell with a single candidate is taken to hold that candidate as its value, unless it is
+/// wrapped in parentheses.
+pub fn from_pencilmarks<T: GridSize>(empty_grid: &Grid<T>, input: &str) -> Result<Grid<T>, GridParseError> {
+
+    let in_entry = |c: char| c.is_ascii_digit() || c == '(' || c == ')' || (T::size() > 9 && c == ',');
+    let entries: Vec<&str> = input.split(|c: char| !in_entry(c)).filter(|s| !s.is_empty()).collect();
+    if entries.len() != T::size() * T::size() {
+        return Err(GridParseError::BadLength);
+    }
+
+    let mut candidates = Vec::new();
+    for (idx, entry) in entries.iter().enumerate() {
+        let unplaced = entry.starts_with('(') && entry.ends_with(')');
+        let entry = entry.trim_start_matches('(').trim_end_matches(')');
+        let digits: Vec<usize> = if T::size() > 9 {
+            entry.split(',').map(|number| number.parse().unwrap_or(0)).collect()
+        } else {
+            entry.bytes().map(|b| b.wrapping_sub(b'0') as usize).collect()
+        };
+        if digits.iter().any(|&d| d == 0 || d > T::size()) {
+            return Err(GridParseError::BadCandidate(idx));
+        }
+        candidates.push((digits, unplaced));
+    }
+
+    let mut grid = empty_grid.clone();
+
+    // Place the values first, so that eliminations arising from them are made.
+    for (idx, &(ref digits, unplaced)) in candidates.iter().enumerate() {
+        if digits.len() == 1 && !unplaced {
+            if grid.has_candidate(idx, digits[0]) {
+                grid.place_value(idx, digits[0]);
+            } else {
+                return Err(GridParseError::Contradiction(idx));
+            }
+        }
+    }
+
+    // Then remove any candidates which are not listed for each unsolved cell.
+    for (idx, &(ref digits, unplaced)) in candidates.iter().enumerate() {
+        if digits.len() > 1 || unplaced {
+            for value in grid.values() {
+                if !digits.contains(&value) {
+                    grid.eliminate_value(idx, value);
+                }
+            }
+            if grid.num_candidates(idx) == 0 {
+                return Err(GridParseError::Contradiction(idx));
+            }
+        }
+    }
+
+    Ok(grid)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use grid::pencilmarks::from_pencilmarks;
+    use grid::variants::{classic_from_pencilmarks, classic_from_string, empty_sixteen_by_sixteen};
+    use solver;
+    use solver::SolveConfiguration;
+    use strategies::Strategy::*;
+
+    #[test]
+    fn test_pencilmark_round_trip() {
+        let mut grid = classic_from_string(".287...5..54..398.........7..1.9......63......9...43......5....5.2.....66..17...9".to_string()).unwrap();
+        solver::solve(&mut grid, &SolveConfiguration::with_strategies(vec![FullHouse, HiddenSingle, NakedSingle, BoxLine]));
+
+        let pencilmarks = grid.to_pencilmark_string();
+        let parsed = classic_from_pencilmarks(pencilmarks.clone()).unwrap();
+        assert_eq!(parsed.to_pencilmark_string(), pencilmarks);
+    }
+
+    #[test]
+    fn test_unplaced_singles_round_trip() {
+        let mut grid = classic_from_string(".287...5..54..398.........7..1.9......63......9...43......5....5.2.....66..17...9".to_string()).unwrap();
+        for value in 2..10 {
+            grid.eliminate_value(0, value);
+        }
+        assert!(grid.is_empty(0) && grid.num_candidates(0) == 1);
+
+        let pencilmarks = grid.to_pencilmark_string();
+        assert!(pencilmarks.contains("(1)"));
+        let parsed = classic_from_pencilmarks(pencilmarks.clone()).unwrap();
+        assert!(parsed.is_empty(0));
+        assert_eq!(parsed.candidates(9), grid.candidates(9));
+        assert_eq!(parsed.to_pencilmark_string(), pencilmarks);
+    }
+
+    #[test]
+    fn test_large_grid_round_trip() {
+        let mut grid = empty_sixteen_by_sixteen();
+        grid.place_value(0, 12);
+        for value in 1..16 {
+            grid.eliminate_value(1, value);
+        }
+
+        let pencilmarks = grid.to_pencilmark_string();
+        assert!(pencilmarks.contains("(16)") && pencilmarks.contains("1,2,3,4,5,6,7,8,9,10,11,13,14,15,16"));
+        let parsed = from_pencilmarks(&empty_sixteen_by_sixteen(), &pencilmarks).unwrap();
+        assert_eq!(parsed.value(0), Some(12));
+        assert!(parsed.is_empty(1));
+        assert_eq!(parsed.to_pencilmark_string(), pencilmarks);
+    }
+}
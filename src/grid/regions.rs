@@ -74,7 +74,7 @@ impl<'a, T: GridSize> Grid<T> {
     /// Return the row which contains all of the given cells
     pub fn row_containing(&self, cells: &CellSet<T>) -> Option<CellSet<T>> {
         for row in self.rows() {
-            if row & cells == *cells { return Some(row.clone()); }
+            if row & cells == *cells { return Some(*row); }
         }
         None
     }
@@ -82,24 +82,24 @@ impl<'a, T: GridSize> Grid<T> {
     /// Return the column which contains all of the given cells
     pub fn column_containing(&self, cells: &CellSet<T>) -> Option<CellSet<T>> {
         for column in self.columns() {
-            if column & cells == *cells { return Some(column.clone()); }
+            if column & cells == *cells { return Some(*column); }
         }
         None
     }
 
     /// Get the rows which intersect the given `CellSet`
     pub fn intersecting_rows(&self, cells: &CellSet<T>) -> Vec<CellSet<T>> {
-        self.rows().iter().filter(|&row| !((row & cells).is_empty())).map(|row| row.clone()).collect()
+        self.rows().iter().filter(|&row| !((row & cells).is_empty())).copied().collect()
     }
 
     /// Get the columns which intersect the given `CellSet`
     pub fn intersecting_columns(&self, cells: &CellSet<T>) -> Vec<CellSet<T>> {
-        self.columns().iter().filter(|&row| !((row & cells).is_empty())).map(|column| column.clone()).collect()
+        self.columns().iter().filter(|&row| !((row & cells).is_empty())).copied().collect()
     }
 
     /// Return all regions which contain all of the given cells
     pub fn all_regions_containing(&self, cells: &CellSet<T>) -> Vec<CellSet<T>> {
-        self.all_regions().iter().filter(|&region| region.contains_all(cells)).map(|region| region.clone()).collect()
+        self.all_regions().iter().filter(|&region| region.contains_all(cells)).copied().collect()
     }
 
     /// Group the cells in the given `CellSet` by either rows or columns
@@ -118,6 +118,6 @@ impl<'a, T: GridSize> Grid<T> {
 
     /// Get the common neighbours of all of the given cells
     pub fn common_neighbours(&self, cells: &CellSet<T>) -> CellSet<T> {
-        CellSet::intersection(&cells.map(|cell| self.neighbours(cell)))
+        cells.iter().fold(CellSet::full(), |acc, cell| acc & self.neighbours(cell))
     }
 }
@@ -0,0 +1,83 @@
+//! Serde support for the bitmask-backed sets, which are written out as plain lists of their
+//! members so that the serialized form does not depend on the internal representation, and for
+//! reading grids back in.
+
+use grid::{Candidate, CellIdx, Grid, GridSize};
+use grid::candidateset::CandidateSet;
+use grid::cell::Cell;
+use grid::cellset::CellSet;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::de::Error;
+use std::convert::TryFrom;
+
+impl<T: GridSize> Serialize for CellSet<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+impl<'de, T: GridSize> Deserialize<'de> for CellSet<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<CellSet<T>, D::Error> {
+        let cells = Vec::<CellIdx>::deserialize(deserializer)?;
+        if let Some(&cell) = cells.iter().find(|&&cell| cell >= T::size() * T::size()) {
+            return Err(D::Error::custom(format!("cell index {} out of range", cell)));
+        }
+        Ok(CellSet::from_cells(cells))
+    }
+}
+
+impl<T: GridSize> Serialize for CandidateSet<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+impl<'de, T: GridSize> Deserialize<'de> for CandidateSet<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<CandidateSet<T>, D::Error> {
+        let candidates = Vec::<Candidate>::deserialize(deserializer)?;
+        if let Some(&candidate) = candidates.iter().find(|&&candidate| candidate == 0 || candidate > T::size()) {
+            return Err(D::Error::custom(format!("candidate {} out of range", candidate)));
+        }
+        Ok(CandidateSet::from_candidates(candidates))
+    }
+}
+
+/// The parts of a `Grid` which are written out. The caches of which cells are empty and which can
+/// hold each value are left out, and rebuilt from the cells when the grid is read back in.
+#[derive(Deserialize)]
+#[serde(bound = "")]
+pub struct GridFields<T: GridSize> {
+    cells: Vec<Cell<T>>,
+    rows: Vec<CellSet<T>>,
+    columns: Vec<CellSet<T>>,
+    extra_regions: Vec<CellSet<T>>,
+    all_regions: Vec<CellSet<T>>,
+    neighbours: Vec<CellSet<T>>,
+}
+
+impl<T: GridSize> TryFrom<GridFields<T>> for Grid<T> {
+    type Error = String;
+
+    fn try_from(fields: GridFields<T>) -> Result<Grid<T>, String> {
+        let num_cells = T::size() * T::size();
+        if fields.cells.len() != num_cells || fields.neighbours.len() != num_cells {
+            return Err(format!("expected {} cells and sets of neighbours", num_cells));
+        }
+
+        let mut grid = Grid {
+            cells: vec![Cell::empty(); num_cells],
+            rows: fields.rows,
+            columns: fields.columns,
+            extra_regions: fields.extra_regions,
+            all_regions: fields.all_regions,
+            neighbours: fields.neighbours,
+            candidate_cells: vec![CellSet::full(); T::size()],
+            empty_cells: CellSet::full(),
+        };
+        for (cell, contents) in fields.cells.into_iter().enumerate() {
+            grid.set_cell(cell, contents);
+        }
+        Ok(grid)
+    }
+}
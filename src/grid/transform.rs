@@ -0,0 +1,309 @@
+//! Symmetry transformations of puzzles and grids.
+
+use rand::Rng;
+
+use grid::{Grid, GridSize};
+use grid::automorphisms::Automorphisms;
+use grid::cell::Cell;
+use grid::cellset::CellSet;
+
+/// A symmetry transformation of a puzzle, made up of a rearrangement of its cells followed by a
+/// relabelling of its digits.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Transform {
+    /// For each cell of the transformed puzzle, the cell of the original puzzle that it comes from.
+    cells: Vec<usize>,
+    /// For each digit of the original puzzle, the digit that it becomes. Empty cells, represented
+    /// by 0, are left empty.
+    digits: Vec<usize>,
+}
+
+impl Transform {
+
+    /// Create a transformation from the cell of the original puzzle that each cell of the
+    /// transformed puzzle comes from, and the digit that each digit of the original puzzle becomes.
+    pub fn new(cells: Vec<usize>, digits: Vec<usize>) -> Transform {
+        Transform { cells, digits }
+    }
+
+    /// The transformation which leaves a puzzle of the given size unchanged.
+    pub fn identity<T: GridSize>() -> Transform {
+        Transform {
+            cells: (0..T::size() * T::size()).collect(),
+            digits: (0..T::size() + 1).collect(),
+        }
+    }
+
+    /// Swap two rows within the same band.
+    pub fn swap_rows<T: GridSize>(row1: usize, row2: usize) -> Transform {
+        let (height, _) = box_shape(T::size());
+        assert_eq!(row1 / height, row2 / height, "Rows {} and {} are in different bands", row1, row2);
+        Transform::rearrangement::<T>(&swapped(T::size(), &[row1], &[row2]), &(0..T::size()).collect::<Vec<_>>(), false)
+    }
+
+    /// Swap two columns within the same stack.
+    pub fn swap_columns<T: GridSize>(column1: usize, column2: usize) -> Transform {
+        let (_, width) = box_shape(T::size());
+        assert_eq!(column1 / width, column2 / width, "Columns {} and {} are in different stacks", column1, column2);
+        Transform::rearrangement::<T>(&(0..T::size()).collect::<Vec<_>>(), &swapped(T::size(), &[column1], &[column2]), false)
+    }
+
+    /// Swap two bands of rows.
+    pub fn swap_bands<T: GridSize>(band1: usize, band2: usize) -> Transform {
+        let (height, _) = box_shape(T::size());
+        let (rows1, rows2): (Vec<usize>, Vec<usize>) = (0..height).map(|row| (band1 * height + row, band2 * height + row)).unzip();
+        Transform::rearrangement::<T>(&swapped(T::size(), &rows1, &rows2), &(0..T::size()).collect::<Vec<_>>(), false)
+    }
+
+    /// Swap two stacks of columns.
+    pub fn swap_stacks<T: GridSize>(stack1: usize, stack2: usize) -> Transform {
+        let (_, width) = box_shape(T::size());
+        let (columns1, columns2): (Vec<usize>, Vec<usize>) = (0..width).map(|column| (stack1 * width + column, stack2 * width + column)).unzip();
+        Transform::rearrangement::<T>(&(0..T::size()).collect::<Vec<_>>(), &swapped(T::size(), &columns1, &columns2), false)
+    }
+
+    /// Reflect the grid in its main diagonal, turning rows into columns. This only maps boxes onto
+    /// boxes when they are square.
+    pub fn transpose<T: GridSize>() -> Transform {
+        let lines: Vec<usize> = (0..T::size()).collect();
+        Transform::rearrangement::<T>(&lines, &lines, true)
+    }
+
+    /// Turn the grid a quarter turn clockwise. Like transposing, this only maps boxes onto boxes
+    /// when they are square.
+    pub fn rotate<T: GridSize>() -> Transform {
+        let lines: Vec<usize> = (0..T::size()).collect();
+        let reversed: Vec<usize> = lines.iter().rev().cloned().collect();
+        Transform::rearrangement::<T>(&lines, &reversed, true)
+    }
+
+    /// Reflect the grid from left to right.
+    pub fn reflect_horizontally<T: GridSize>() -> Transform {
+        let lines: Vec<usize> = (0..T::size()).collect();
+        let reversed: Vec<usize> = lines.iter().rev().cloned().collect();
+        Transform::rearrangement::<T>(&lines, &reversed, false)
+    }
+
+    /// Reflect the grid from top to bottom.
+    pub fn reflect_vertically<T: GridSize>() -> Transform {
+        let lines: Vec<usize> = (0..T::size()).collect();
+        let reversed: Vec<usize> = lines.iter().rev().cloned().collect();
+        Transform::rearrangement::<T>(&reversed, &lines, false)
+    }
+
+    /// Relabel the digits, where `digits` lists the new label for each of the digits 1 to the size
+    /// of the grid in turn.
+    pub fn relabel<T: GridSize>(digits: &[usize]) -> Transform {
+        let mut sorted = digits.to_vec();
+        sorted.sort();
+        assert_eq!(sorted, (1..T::size() + 1).collect::<Vec<_>>(), "Not a relabelling of the digits: {:?}", digits);
+        Transform {
+            cells: (0..T::size() * T::size()).collect(),
+            digits: Some(0).into_iter().chain(digits.iter().cloned()).collect(),
+        }
+    }
+
+    /// A random symmetry of a standard Sudoku - a random rearrangement of the bands, the rows
+    /// within each band, the stacks and the columns within each stack, transposed half the time
+    /// when the boxes are square, followed by a random relabelling of the digits.
+    pub fn random<T: GridSize, R: Rng + ?Sized>(rng: &mut R) -> Transform {
+        let (height, width) = box_shape(T::size());
+        let rows = random_band_preserving_order(T::size(), height, rng);
+        let columns = random_band_preserving_order(T::size(), width, rng);
+        let transposed = height == width && rng.gen_range(0, 2usize) == 1;
+        Transform::rearrangement::<T>(&rows, &columns, transposed).then(&Transform::random_relabelling::<T, R>(rng))
+    }
+
+    /// A random symmetry of the layout of the given grid, followed by a random relabelling of the
+    /// digits, so that applying it to a puzzle on the grid gives an equivalent puzzle on the same
+    /// grid.
+    ///
+    /// This finds the symmetries of the layout each time, so to make many transforms for the same
+    /// layout, find them once and use `random_for_automorphisms` instead.
+    pub fn random_for_grid<T: GridSize, R: Rng + ?Sized>(grid: &Grid<T>, rng: &mut R) -> Transform {
+        Transform::random_for_automorphisms::<T, R>(&grid.automorphisms(), rng)
+    }
+
+    /// A random one of the given symmetries of a layout, followed by a random relabelling of the
+    /// digits.
+    pub fn random_for_automorphisms<T: GridSize, R: Rng + ?Sized>(automorphisms: &Automorphisms, rng: &mut R) -> Transform {
+        match *automorphisms {
+            Automorphisms::Standard => Transform::random::<T, R>(rng),
+            Automorphisms::Listed(ref automorphisms) => {
+                let automorphism = rng.choose(automorphisms).unwrap();
+                automorphism.then(&Transform::random_relabelling::<T, R>(rng))
+            },
+        }
+    }
+
+    /// Rearrange the rows and columns, taking each row of the transformed grid from the given row
+    /// of the original and likewise for columns, after transposing the original if asked.
+    fn rearrangement<T: GridSize>(rows: &[usize], columns: &[usize], transposed: bool) -> Transform {
+        let size = T::size();
+        let cells = (0..size * size).map(|cell| {
+            let (row, column) = (rows[cell / size], columns[cell % size]);
+            if transposed { column * size + row } else { row * size + column }
+        }).collect();
+        Transform { cells, digits: (0..size + 1).collect() }
+    }
+
+    /// A random relabelling of the digits.
+    fn random_relabelling<T: GridSize, R: Rng + ?Sized>(rng: &mut R) -> Transform {
+        let mut digits: Vec<usize> = (1..T::size() + 1).collect();
+        rng.shuffle(&mut digits);
+        Transform::relabel::<T>(&digits)
+    }
+
+    /// For each cell of the transformed puzzle, the cell of the original puzzle that it comes from.
+    pub fn cells(&self) -> &[usize] {
+        &self.cells
+    }
+
+    /// For each digit of the original puzzle, the digit that it becomes.
+    pub fn digits(&self) -> &[usize] {
+        &self.digits
+    }
+
+    /// Apply the transformation to the given puzzle or solution.
+    pub fn apply(&self, puzzle: &[usize]) -> Vec<usize> {
+        self.cells.iter().map(|&cell| self.digits[puzzle[cell]]).collect()
+    }
+
+    /// Apply the transformation to a grid, moving its values and candidates along with its layout.
+    /// The transformation must map rows and columns onto rows and columns, as all of the symmetries
+    /// of a Sudoku do.
+    pub fn apply_to_grid<T: GridSize>(&self, grid: &Grid<T>) -> Grid<T> {
+
+        let mut destinations = vec![0; self.cells.len()];
+        for (destination, &source) in self.cells.iter().enumerate() {
+            destinations[source] = destination;
+        }
+        let image = |set: &CellSet<T>| CellSet::from_cells(set.iter().map(|cell| destinations[cell]));
+
+        let regions: Vec<CellSet<T>> = grid.extra_regions().iter().map(&image).collect();
+        let neighbours: Vec<CellSet<T>> = self.cells.iter().map(|&source| image(grid.neighbours(source))).collect();
+        let mut transformed = Grid::empty(&regions, &neighbours);
+
+        for (cell, &source) in self.cells.iter().enumerate() {
+            let mut transformed_cell = Cell::empty();
+            match grid.value(source) {
+                Some(value) => transformed_cell.set_value(self.digits[value]),
+                None => for digit in 1..T::size() + 1 {
+                    if !grid.has_candidate(source, digit) {
+                        transformed_cell.remove_candidate(self.digits[digit]);
+                    }
+                },
+            }
+            transformed.set_cell(cell, transformed_cell);
+        }
+
+        transformed
+    }
+
+    /// The transformation which undoes this one, which can be used to map the solution of a
+    /// transformed puzzle back to a solution of the original.
+    pub fn inverse(&self) -> Transform {
+        let (mut cells, mut digits) = (vec![0; self.cells.len()], vec![0; self.digits.len()]);
+        for (new_cell, &old_cell) in self.cells.iter().enumerate() {
+            cells[old_cell] = new_cell;
+        }
+        for (old_digit, &new_digit) in self.digits.iter().enumerate() {
+            digits[new_digit] = old_digit;
+        }
+        Transform { cells, digits }
+    }
+
+    /// The transformation which applies this one followed by the other.
+    pub fn then(&self, other: &Transform) -> Transform {
+        Transform {
+            cells: other.cells.iter().map(|&cell| self.cells[cell]).collect(),
+            digits: self.digits.iter().map(|&digit| other.digits[digit]).collect(),
+        }
+    }
+}
+
+/// The height and width of the boxes of a grid of the given size - as square as possible, and
+/// wider than they are tall.
+pub fn box_shape(size: usize) -> (usize, usize) {
+    let height = (1..size + 1).filter(|&height| size.is_multiple_of(height) && height * height <= size).max().unwrap_or(1);
+    (height, size / height)
+}
+
+/// All of the orderings of `size` rows (or columns) which keep each band of `band` consecutive rows
+/// together.
+pub fn band_preserving_orders(size: usize, band: usize) -> Vec<Vec<usize>> {
+    let within_band_orders = permutations(band);
+    permutations(size / band).iter().flat_map(|band_order| {
+        band_order.iter().fold(vec![vec![]], |orders: Vec<Vec<usize>>, &band_idx| {
+            orders.iter().flat_map(|order| within_band_orders.iter().map(move |within| {
+                let mut order = order.clone();
+                order.extend(within.iter().map(|&line| band_idx * band + line));
+                order
+            })).collect()
+        })
+    }).collect()
+}
+
+/// A random ordering of `size` rows (or columns) which keeps each band of `band` consecutive rows
+/// together.
+fn random_band_preserving_order<R: Rng + ?Sized>(size: usize, band: usize, rng: &mut R) -> Vec<usize> {
+    let mut bands: Vec<usize> = (0..size / band).collect();
+    rng.shuffle(&mut bands);
+    bands.iter().flat_map(|&band_idx| {
+        let mut lines: Vec<usize> = (band_idx * band..(band_idx + 1) * band).collect();
+        rng.shuffle(&mut lines);
+        lines
+    }).collect()
+}
+
+/// The ordering of `size` lines which swaps each of the first lines with the corresponding second
+/// line.
+pub fn swapped(size: usize, first: &[usize], second: &[usize]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..size).collect();
+    for (&line1, &line2) in first.iter().zip(second.iter()) {
+        order.swap(line1, line2);
+    }
+    order
+}
+
+/// A random isomorph of the given grid - an equivalent puzzle on the same layout, with its cells
+/// rearranged by a random symmetry of the layout and its digits relabelled at random.
+pub fn random_isomorph<T: GridSize, R: Rng + ?Sized>(grid: &Grid<T>, rng: &mut R) -> Grid<T> {
+    Transform::random_for_grid(grid, rng).apply_to_grid(grid)
+}
+
+/// All of the orderings of the numbers below `n`.
+fn permutations(n: usize) -> Vec<Vec<usize>> {
+    (0..n).fold(vec![vec![]], |orders, _| {
+        orders.iter().flat_map(|order: &Vec<usize>| (0..n).filter(move |idx| !order.contains(idx)).map(move |idx| {
+            let mut order = order.clone();
+            order.push(idx);
+            order
+        })).collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+
+    use analyser::rate;
+    use grid::variants::*;
+    use rand::{SeedableRng, StdRng};
+    use solver::SolveConfiguration;
+    use super::*;
+
+    #[test]
+    fn test_random_isomorphs_keep_rating() {
+        let grid = classic_from_string(".9...4.2...1.8....6.21.....4....57..1..7.9..2..84....5.....65.8....7.6...5.3...4.".to_string()).unwrap();
+        let configuration = SolveConfiguration::with_all_strategies();
+        let score = rate(&grid, &configuration).unwrap().score;
+        let (automorphisms, mut rng) = (grid.automorphisms(), StdRng::from_seed([7; 32]));
+        for _ in 0..5 {
+            let transform = Transform::random_for_automorphisms::<Grid9, _>(&automorphisms, &mut rng);
+            let isomorph = transform.apply_to_grid(&grid);
+            assert_eq!(isomorph.to_clues(), transform.apply(&grid.to_clues()));
+            assert_eq!(transform.inverse().apply(&isomorph.to_clues()), grid.to_clues());
+            assert_eq!(rate(&isomorph, &configuration).unwrap().score, score);
+        }
+    }
+}
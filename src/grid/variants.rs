@@ -3,15 +3,20 @@
 use crate::define_grid_size;
 use grid::{CellIdx, Grid, GridSize};
 use grid::cellset::CellSet;
+use grid::pencilmarks::from_pencilmarks;
+use grid::transform::box_shape;
 
 use std::fmt;
 
 define_grid_size!(Grid6, 6);
 define_grid_size!(Grid9, 9);
+define_grid_size!(Grid16, 16);
+define_grid_size!(Grid25, 25);
 
 /// Errors that can arise when reading in a grid from a string representation.
 pub enum GridParseError {
     BadLength,
+    BadCandidate(CellIdx),
     Contradiction(CellIdx),
 }
 
@@ -20,6 +25,7 @@ impl fmt::Display for GridParseError {
         use self::GridParseError::*;
         match *self {
             BadLength => write!(f, "The grid does not have the expected length"),
+            BadCandidate(pos) => write!(f, "The cell at position {} has a candidate which is out of range", pos),
             Contradiction(pos) => write!(f, "The clue at position {} contradicts the others", pos),
         }
     }
@@ -52,6 +58,10 @@ pub fn classic_from_clues(clues: &[usize]) -> Result<Grid<Grid9>, GridParseError
     grid_from_empty_grid_and_clues(&empty_classic(), clues)
 }
 
+pub fn classic_from_pencilmarks(input: String) -> Result<Grid<Grid9>, GridParseError> {
+    from_pencilmarks(&empty_classic(), &input)
+}
+
 // 6x6 Sudoku
 
 pub fn empty_six_by_six() -> Grid<Grid6> {
@@ -73,6 +83,28 @@ pub fn six_by_six_from_clues(clues: &[usize]) -> Result<Grid<Grid6>, GridParseEr
     grid_from_empty_grid_and_clues(&empty_six_by_six(), clues)
 }
 
+pub fn six_by_six_from_pencilmarks(input: String) -> Result<Grid<Grid6>, GridParseError> {
+    from_pencilmarks(&empty_six_by_six(), &input)
+}
+
+// 16x16 and 25x25 Sudoku
+
+pub fn empty_sixteen_by_sixteen() -> Grid<Grid16> {
+    empty_with_boxes()
+}
+
+pub fn sixteen_by_sixteen_from_clues(clues: &[usize]) -> Result<Grid<Grid16>, GridParseError> {
+    grid_from_empty_grid_and_clues(&empty_sixteen_by_sixteen(), clues)
+}
+
+pub fn empty_twenty_five_by_twenty_five() -> Grid<Grid25> {
+    empty_with_boxes()
+}
+
+pub fn twenty_five_by_twenty_five_from_clues(clues: &[usize]) -> Result<Grid<Grid25>, GridParseError> {
+    grid_from_empty_grid_and_clues(&empty_twenty_five_by_twenty_five(), clues)
+}
+
 // Irregular Sudoku
 
 pub fn empty_irregular(regions: &[Vec<usize>]) -> Grid<Grid9> {
@@ -399,7 +431,20 @@ pub fn odd_even_from_clues(clues: &[usize], odds: &[usize], evens: &[usize]) ->
 
 // Helpers
 
-fn grid_from_empty_grid_and_string<T: GridSize>(empty_grid: &Grid<T>, input: String) -> Result<Grid<T>, GridParseError> {
+/// An empty grid of the given size whose regions are boxes of the usual shape.
+fn empty_with_boxes<T: GridSize>() -> Grid<T> {
+
+    let (size, (height, width)) = (T::size(), box_shape(T::size()));
+    let grid_regions: Vec<CellSet<T>> = (0..size)
+            .map(|idx| CellSet::from_cells((0..size).map(|cell| (height * (idx / height) + cell / width) * size + width * (idx % height) + cell % width)))
+            .collect();
+
+    Grid::empty(&grid_regions, &vec![CellSet::empty(); size * size])
+}
+
+/// Read in a grid with the layout of the given empty grid, from a string of its clues. On grids
+/// larger than 9x9 the clues are separated by commas.
+pub fn grid_from_empty_grid_and_string<T: GridSize>(empty_grid: &Grid<T>, input: String) -> Result<Grid<T>, GridParseError> {
     if T::size() <= 9 {
         let clues: Vec<usize> = input.bytes().map(|byte| match byte {
             b'1'..=b'9' => (byte - b'0') as usize,
@@ -407,11 +452,23 @@ fn grid_from_empty_grid_and_string<T: GridSize>(empty_grid: &Grid<T>, input: Str
         }).collect();
         grid_from_empty_grid_and_clues(empty_grid, &clues)
     } else {
-        unimplemented!();
+        let mut clues = Vec::new();
+        for (idx, entry) in input.split(',').map(|entry| entry.trim()).enumerate() {
+            match entry {
+                "" | "." | "0" => clues.push(0),
+                _ => match entry.parse() {
+                    Ok(clue) if clue <= T::size() => clues.push(clue),
+                    _ => return Err(GridParseError::BadCandidate(idx)),
+                },
+            }
+        }
+        grid_from_empty_grid_and_clues(empty_grid, &clues)
     }
 }
 
-fn grid_from_empty_grid_and_clues<T: GridSize>(grid: &Grid<T>, clues: &[usize]) -> Result<Grid<T>, GridParseError> {
+/// Place the given clues in a copy of the given grid. Clues matching a value already in the grid
+/// are allowed, so that the grid may be a starting grid with some values already given.
+pub fn grid_from_empty_grid_and_clues<T: GridSize>(grid: &Grid<T>, clues: &[usize]) -> Result<Grid<T>, GridParseError> {
 
     let mut grid = grid.clone();
 
@@ -420,7 +477,7 @@ fn grid_from_empty_grid_and_clues<T: GridSize>(grid: &Grid<T>, clues: &[usize])
     }
 
     for (idx, clue) in clues.iter().enumerate() {
-        if *clue > 0 {
+        if *clue > 0 && grid.value(idx) != Some(*clue) {
             if grid.has_candidate(idx, *clue) {
                 grid.place_value(idx, *clue);
             } else {
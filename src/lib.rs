@@ -3,10 +3,15 @@
 extern crate ansi_term;
 extern crate itertools;
 extern crate rand;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(all(test, feature = "serde"))]
+extern crate serde_json;
 
 pub mod analyser;
 pub mod generator;
 pub mod grid;
+pub mod render;
 pub mod solver;
 pub mod strategies;
 mod utils;
\ No newline at end of file
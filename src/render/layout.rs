@@ -0,0 +1,90 @@
+//! Work out how the regions of a grid should be drawn.
+
+use grid::{CellIdx, Grid, GridSize};
+use grid::cellset::CellSet;
+use grid::transform::box_shape;
+
+/// The number of columns used when laying out the candidates of a single cell.
+pub fn candidate_columns<T: GridSize>() -> usize {
+    (1..).find(|&columns| columns * columns >= T::size()).unwrap()
+}
+
+/// The number of rows used when laying out the candidates of a single cell.
+pub fn candidate_rows<T: GridSize>() -> usize {
+    T::size().div_ceil(candidate_columns::<T>())
+}
+
+/// Label each cell with the box that it is part of. The boxes are the usual boxes for a grid of
+/// this size when those are all regions of the grid, and otherwise - as in an irregular Sudoku -
+/// the full-sized extra regions which overlap no other. Cells which are not part of any box are
+/// labelled with their own index, so that they are drawn with a thick border on every side.
+pub fn box_indices<T: GridSize>(grid: &Grid<T>) -> Vec<usize> {
+
+    let (size, (height, width)) = (T::size(), box_shape(T::size()));
+    let num_cells = size * size;
+
+    let standard_box = |cell: CellIdx| (cell / size / height) * (size / width) + (cell % size) / width;
+    let standard_boxes: Vec<CellSet<T>> = (0..size)
+        .map(|idx| CellSet::from_cells((0..num_cells).filter(|&cell| standard_box(cell) == idx)))
+        .collect();
+    if standard_boxes.iter().all(|region| grid.extra_regions().contains(region)) {
+        return (0..num_cells).map(standard_box).collect();
+    }
+
+    let full_sized: Vec<&CellSet<T>> = grid.extra_regions().iter().filter(|region| region.len() == size).collect();
+    let mut indices: Vec<usize> = (0..num_cells).map(|cell| num_cells + cell).collect();
+    for (idx, region) in full_sized.iter().enumerate() {
+        if full_sized.iter().all(|other| other == region || (*other & *region).is_empty()) {
+            for cell in region.iter() {
+                indices[cell] = idx;
+            }
+        }
+    }
+
+    indices
+}
+
+/// Find the extra regions of the grid which form a solid rectangle but which are not among the
+/// boxes of the grid - for example, the windows of a Windoku.
+pub fn windows<T: GridSize>(grid: &Grid<T>) -> Vec<CellSet<T>> {
+
+    let boxes = box_indices(grid);
+
+    grid.extra_regions().iter()
+        .filter(|region| !region.is_empty())
+        .filter(|region| region.iter().any(|cell| boxes[cell] != boxes[region.first().unwrap()]))
+        .filter(|region| {
+            let rows: Vec<_> = region.map(|cell| cell / T::size());
+            let cols: Vec<_> = region.map(|cell| cell % T::size());
+            let height = rows.iter().max().unwrap() - rows.iter().min().unwrap() + 1;
+            let width = cols.iter().max().unwrap() - cols.iter().min().unwrap() + 1;
+            height * width == region.len()
+        })
+        .cloned()
+        .collect()
+}
+
+/// Find the extra regions of the grid which form a line of diagonally-adjacent cells, with one cell
+/// in each column (or in each row), and return the cells of each such line in order.
+pub fn diagonals<T: GridSize>(grid: &Grid<T>) -> Vec<Vec<CellIdx>> {
+
+    let size = T::size();
+    let (row, col) = (|cell: &CellIdx| cell / size, |cell: &CellIdx| cell % size);
+
+    grid.extra_regions().iter()
+        .filter(|region| region.len() == size)
+        .filter_map(|region| {
+            let by_column = line_through(region.map(|cell| cell), col, row);
+            by_column.or_else(|| line_through(region.map(|cell| cell), row, col))
+        })
+        .collect()
+}
+
+/// Order the given cells by the first coordinate, and check that there is one cell for each value
+/// of that coordinate and that the second coordinate moves by exactly one between each cell.
+fn line_through<F: Fn(&CellIdx) -> usize, G: Fn(&CellIdx) -> usize>(mut cells: Vec<CellIdx>, along: F, across: G) -> Option<Vec<CellIdx>> {
+    cells.sort_by_key(&along);
+    let one_each = cells.iter().enumerate().all(|(idx, cell)| along(cell) == idx);
+    let adjacent = cells.windows(2).all(|pair| across(&pair[0]) + 1 == across(&pair[1]) || across(&pair[1]) + 1 == across(&pair[0]));
+    if one_each && adjacent { Some(cells) } else { None }
+}
@@ -0,0 +1,290 @@
+//! Render grids, and the steps taken to solve them, as SVG images.
+//!
+//! The output is plain SVG text, so that no graphics libraries are needed to produce pictures of
+//! a solve.
+
+mod layout;
+
+use grid::{Candidate, CellIdx, Grid, GridSize};
+use grid::cellset::CellSet;
+use strategies::{CandidateRole, Deduction, Step, StepAnnotations};
+
+use std::fmt::Write;
+
+/// Options controlling how a grid is drawn.
+pub struct RenderOptions {
+    /// The width and height of a single cell, in pixels.
+    pub cell_size: usize,
+    /// Whether to draw the candidates of empty cells.
+    pub show_candidates: bool,
+    /// Cells which are marked as holding an odd digit.
+    pub odd_cells: Vec<CellIdx>,
+    /// Cells which are marked as holding an even digit.
+    pub even_cells: Vec<CellIdx>,
+}
+
+impl Default for RenderOptions {
+    fn default() -> RenderOptions {
+        RenderOptions { cell_size: 60, show_candidates: true, odd_cells: vec![], even_cells: vec![] }
+    }
+}
+
+const MARGIN: usize = 10;
+
+const HIGHLIGHTED_CELL_COLOUR: &str = "#fff6b3";
+const WINDOW_COLOUR: &str = "#dde8f5";
+const ODD_EVEN_COLOUR: &str = "#d9d9d9";
+const DIAGONAL_COLOUR: &str = "#9bb7d4";
+const PLACEMENT_COLOUR: &str = "#6fcf6f";
+const ELIMINATION_COLOUR: &str = "#f08080";
+
+/// Draw the given grid, including its candidates and any decorations needed by its variant.
+pub fn grid_to_svg<T: GridSize>(grid: &Grid<T>, options: &RenderOptions) -> String {
+    render(grid, None, options)
+}
+
+/// Draw the given grid, highlighting the pattern behind the given step and the deductions that
+/// it makes.
+pub fn step_to_svg<T: GridSize>(grid: &Grid<T>, step: &Step<T>, options: &RenderOptions) -> String {
+    render(grid, Some(step.annotations(grid)), options)
+}
+
+fn render<T: GridSize>(grid: &Grid<T>, annotations: Option<StepAnnotations<T>>, options: &RenderOptions) -> String {
+
+    let size = 2 * MARGIN + T::size() * options.cell_size;
+    let mut svg = String::new();
+
+    writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{0}" viewBox="0 0 {0} {0}">"#, size).unwrap();
+    writeln!(svg, r#"<defs><marker id="arrow" viewBox="0 0 10 10" refX="10" refY="5" markerWidth="6" markerHeight="6" orient="auto-start-reverse"><path d="M 0 0 L 10 5 L 0 10 z" fill="context-stroke"/></marker></defs>"#).unwrap();
+    writeln!(svg, r#"<rect x="0" y="0" width="{0}" height="{0}" fill="white"/>"#, size).unwrap();
+
+    // Background shading, drawn underneath everything else.
+    for window in layout::windows(grid) {
+        for cell in window.iter() {
+            draw_cell_fill::<T>(&mut svg, cell, WINDOW_COLOUR, options);
+        }
+    }
+    if let Some(ref annotations) = annotations {
+        for cell in annotations.cells.iter() {
+            draw_cell_fill::<T>(&mut svg, cell, HIGHLIGHTED_CELL_COLOUR, options);
+        }
+    }
+    for &cell in &options.odd_cells {
+        let (x, y) = cell_centre::<T>(cell, options);
+        writeln!(svg, r#"<circle cx="{}" cy="{}" r="{}" fill="{}"/>"#, x, y, 0.4 * options.cell_size as f64, ODD_EVEN_COLOUR).unwrap();
+    }
+    for &cell in &options.even_cells {
+        let (x, y) = cell_centre::<T>(cell, options);
+        let half = 0.4 * options.cell_size as f64;
+        writeln!(svg, r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#, x - half, y - half, 2.0 * half, 2.0 * half, ODD_EVEN_COLOUR).unwrap();
+    }
+    for diagonal in layout::diagonals(grid) {
+        let points: Vec<String> = diagonal.iter().map(|&cell| { let (x, y) = cell_centre::<T>(cell, options); format!("{},{}", x, y) }).collect();
+        writeln!(svg, r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="3"/>"#, points.join(" "), DIAGONAL_COLOUR).unwrap();
+    }
+
+    // Highlighted candidates, underneath the candidates themselves.
+    if let Some(ref annotations) = annotations {
+        for candidate in &annotations.candidates {
+            draw_candidate_marker::<T>(&mut svg, candidate.cell, candidate.value, role_colour(candidate.role), options);
+        }
+        for deduction in &annotations.deductions {
+            match *deduction {
+                Deduction::Placement(cell, value) => draw_candidate_marker::<T>(&mut svg, cell, value, PLACEMENT_COLOUR, options),
+                Deduction::Elimination(cell, value) => draw_candidate_marker::<T>(&mut svg, cell, value, ELIMINATION_COLOUR, options),
+                Deduction::Contradiction => {},
+            }
+        }
+    }
+
+    draw_grid_lines(&mut svg, grid, options);
+    draw_contents(&mut svg, grid, options);
+
+    if let Some(ref annotations) = annotations {
+        for link in &annotations.links {
+            let (x1, y1) = group_position(&link.from_cells, link.from_value, options);
+            let (x2, y2) = group_position(&link.to_cells, link.to_value, options);
+            let dash = if link.strong { "" } else { r#" stroke-dasharray="4,3""# };
+            writeln!(svg, r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="black" stroke-width="1.5"{} marker-end="url(#arrow)"/>"#, x1, y1, x2, y2, dash).unwrap();
+        }
+    }
+
+    writeln!(svg, "</svg>").unwrap();
+    svg
+}
+
+/// Draw the thin lines between cells, and the thick lines around the boxes of the grid.
+fn draw_grid_lines<T: GridSize>(svg: &mut String, grid: &Grid<T>, options: &RenderOptions) {
+
+    let (size, cs) = (T::size(), options.cell_size);
+    let boxes = layout::box_indices(grid);
+
+    for row in 0..size {
+        for col in 0..size {
+            let (x, y) = (MARGIN + col * cs, MARGIN + row * cs);
+            let thick_right = col + 1 == size || boxes[row * size + col] != boxes[row * size + col + 1];
+            let thick_below = row + 1 == size || boxes[row * size + col] != boxes[(row + 1) * size + col];
+            writeln!(svg, r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="black" stroke-width="{}"/>"#, x + cs, y, x + cs, y + cs, if thick_right { 3 } else { 1 }).unwrap();
+            writeln!(svg, r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="black" stroke-width="{}"/>"#, x, y + cs, x + cs, y + cs, if thick_below { 3 } else { 1 }).unwrap();
+        }
+    }
+
+    writeln!(svg, r#"<line x1="{0}" y1="{0}" x2="{1}" y2="{0}" stroke="black" stroke-width="3"/>"#, MARGIN, MARGIN + size * cs).unwrap();
+    writeln!(svg, r#"<line x1="{0}" y1="{0}" x2="{0}" y2="{1}" stroke="black" stroke-width="3"/>"#, MARGIN, MARGIN + size * cs).unwrap();
+}
+
+/// Draw the values and candidates held in each cell of the grid.
+fn draw_contents<T: GridSize>(svg: &mut String, grid: &Grid<T>, options: &RenderOptions) {
+    for cell in grid.cells().iter() {
+        if let Some(value) = grid.value(cell) {
+            let (x, y) = cell_centre::<T>(cell, options);
+            writeln!(svg, r#"<text x="{}" y="{}" font-family="sans-serif" font-size="{}" text-anchor="middle" dominant-baseline="central">{}</text>"#, x, y, 0.6 * options.cell_size as f64, value).unwrap();
+        } else if options.show_candidates {
+            let font_size = options.cell_size as f64 / (layout::candidate_columns::<T>() as f64 + 1.0);
+            for candidate in grid.candidates(cell).iter() {
+                let (x, y) = candidate_position::<T>(cell, candidate, options);
+                writeln!(svg, r##"<text x="{}" y="{}" font-family="sans-serif" font-size="{}" fill="#444" text-anchor="middle" dominant-baseline="central">{}</text>"##, x, y, font_size, candidate).unwrap();
+            }
+        }
+    }
+}
+
+fn draw_cell_fill<T: GridSize>(svg: &mut String, cell: CellIdx, colour: &str, options: &RenderOptions) {
+    let (row, col) = (cell / T::size(), cell % T::size());
+    writeln!(svg, r#"<rect x="{}" y="{}" width="{2}" height="{2}" fill="{3}"/>"#, MARGIN + col * options.cell_size, MARGIN + row * options.cell_size, options.cell_size, colour).unwrap();
+}
+
+fn draw_candidate_marker<T: GridSize>(svg: &mut String, cell: CellIdx, value: Candidate, colour: &str, options: &RenderOptions) {
+    let (x, y) = candidate_position::<T>(cell, value, options);
+    let radius = 0.45 * options.cell_size as f64 / layout::candidate_columns::<T>() as f64;
+    writeln!(svg, r#"<circle cx="{}" cy="{}" r="{}" fill="{}"/>"#, x, y, radius, colour).unwrap();
+}
+
+fn role_colour(role: CandidateRole) -> &'static str {
+    match role {
+        CandidateRole::Pattern => "#8fb8ff",
+        CandidateRole::Fin => "#d4a5ff",
+        CandidateRole::Pivot => "#ffc46b",
+        CandidateRole::Pincer => "#9ee6b8",
+        CandidateRole::On => "#9ee6b8",
+        CandidateRole::Off => "#8fb8ff",
+    }
+}
+
+fn cell_centre<T: GridSize>(cell: CellIdx, options: &RenderOptions) -> (f64, f64) {
+    let (row, col) = (cell / T::size(), cell % T::size());
+    let cs = options.cell_size as f64;
+    (MARGIN as f64 + (col as f64 + 0.5) * cs, MARGIN as f64 + (row as f64 + 0.5) * cs)
+}
+
+fn candidate_position<T: GridSize>(cell: CellIdx, candidate: Candidate, options: &RenderOptions) -> (f64, f64) {
+    let (row, col) = (cell / T::size(), cell % T::size());
+    let (columns, rows) = (layout::candidate_columns::<T>(), layout::candidate_rows::<T>());
+    let cs = options.cell_size as f64;
+    let (sub_row, sub_col) = ((candidate - 1) / columns, (candidate - 1) % columns);
+    (
+        MARGIN as f64 + col as f64 * cs + (sub_col as f64 + 0.5) * cs / columns as f64,
+        MARGIN as f64 + row as f64 * cs + (sub_row as f64 + 0.5) * cs / rows as f64,
+    )
+}
+
+/// The point at which a link to or from the given group of candidates should be drawn.
+fn group_position<T: GridSize>(cells: &CellSet<T>, value: Candidate, options: &RenderOptions) -> (f64, f64) {
+    let positions = cells.map(|cell| candidate_position::<T>(cell, value, options));
+    let count = positions.len().max(1) as f64;
+    (positions.iter().map(|p| p.0).sum::<f64>() / count, positions.iter().map(|p| p.1).sum::<f64>() / count)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use grid::variants::{classic_from_pencilmarks, classic_from_string, empty_irregular, empty_windoku};
+    use strategies::Strategy;
+    use super::*;
+
+    fn count(svg: &str, pattern: &str) -> usize {
+        svg.lines().filter(|line| line.contains(pattern)).count()
+    }
+
+    /// The elements of the drawing, ignoring the order in which they're drawn.
+    fn elements(svg: &str) -> Vec<&str> {
+        let mut lines: Vec<&str> = svg.lines().collect();
+        lines.sort();
+        lines
+    }
+
+    #[test]
+    fn test_grid_to_svg() {
+        let grid = classic_from_string("..516....6...73...3....57.6....3.691139756482862491..74.1.....5...5....8.....72..".to_string()).unwrap();
+        let svg = grid_to_svg(&grid, &RenderOptions::default());
+        let num_candidates: usize = grid.empty_cells().iter().map(|cell| grid.candidates(cell).len()).sum();
+
+        assert!(svg.starts_with("<svg") && svg.ends_with("</svg>\n"));
+        assert_eq!(count(&svg, r#"font-size="36""#), 81 - grid.empty_cells().len());
+        assert_eq!(count(&svg, r##"fill="#444""##), num_candidates);
+        assert!(svg.contains(r#"<text x="220" y="40" font-family="sans-serif" font-size="36" text-anchor="middle" dominant-baseline="central">1</text>"#));
+
+        // A thick line to the right of and below each third cell, and around the top and left.
+        assert_eq!(count(&svg, r#"stroke-width="3""#), 2 * 27 + 2);
+        assert_eq!(count(&svg, WINDOW_COLOUR), 0);
+        assert_eq!(count(&svg, "<circle"), 0);
+
+        let options = RenderOptions { show_candidates: false, ..RenderOptions::default() };
+        assert_eq!(count(&grid_to_svg(&grid, &options), r##"fill="#444""##), 0);
+    }
+
+    #[test]
+    fn test_box_layout() {
+        // The windows of a Windoku are shaded, but don't change where the boxes are.
+        let windoku = grid_to_svg(&empty_windoku(), &RenderOptions::default());
+        assert_eq!(count(&windoku, WINDOW_COLOUR), 36);
+        assert_eq!(count(&windoku, r#"stroke-width="3""#), 2 * 27 + 2);
+        let mut regions = empty_windoku().extra_regions().to_vec();
+        regions.reverse();
+        assert_eq!(elements(&grid_to_svg(&Grid::empty(&regions, &[CellSet::empty(); 81]), &RenderOptions::default())), elements(&windoku));
+
+        // The boxes of an irregular grid are its regions, whatever order they're given in.
+        let mut regions: Vec<Vec<usize>> = (0..9)
+            .map(|idx| (0..81).filter(|&cell| (cell / 27) * 3 + ((cell % 9 + cell / 9) % 9) / 3 == idx).collect())
+            .collect();
+        let irregular = grid_to_svg(&empty_irregular(&regions), &RenderOptions::default());
+        regions.reverse();
+        assert_eq!(elements(&grid_to_svg(&empty_irregular(&regions), &RenderOptions::default())), elements(&irregular));
+        assert_eq!(count(&irregular, r#"stroke-width="3""#), 80);
+    }
+
+    #[test]
+    fn test_step_to_svg() {
+        let grid = classic_from_pencilmarks("
+            +------------------+------------------+----------------+
+            | 9     6    48   | 258   28   1    | 3   7    45   |
+            | 134   14   2    | 35    9    7    | 8   6    45   |
+            | 38    5    7    | 6     4    38   | 12  129  19   |
+            +------------------+------------------+----------------+
+            | 18    2    1368 | 3489  5    3489 | 7   189  169  |
+            | 7     489  348  | 1     38   6    | 5   89   2    |
+            | 5     189  168  | 289   7    289  | 4   3    1689 |
+            +------------------+------------------+----------------+
+            | 1248  7    14   | 2348  6    2348 | 9   5    138  |
+            | 24    3    5    | 2489  1    2489 | 6   28   7    |
+            | 6     18   9    | 7     238  5    | 12  4    13   |
+            +------------------+------------------+----------------+".to_string()).unwrap();
+        let step = Strategy::XChain.find_steps(&grid).next().unwrap();
+        assert_eq!(step.get_description(&grid), "X-Chain - -8r6c9 --> +8r7c9 --> -8r7c1 --> +8r9c2");
+        let svg = step_to_svg(&grid, &step, &RenderOptions::default());
+
+        // The cells of the chain are highlighted, with its candidates marked by whether they're on
+        // or off, and the elimination of 8 from r6c2.
+        assert_eq!(count(&svg, HIGHLIGHTED_CELL_COLOUR), 4);
+        assert_eq!(count(&svg, role_colour(CandidateRole::On)), 2);
+        assert_eq!(count(&svg, role_colour(CandidateRole::Off)), 2);
+        assert_eq!(count(&svg, ELIMINATION_COLOUR), 1);
+        assert!(svg.contains(&format!(r#"<circle cx="100" cy="360" r="9" fill="{}"/>"#, ELIMINATION_COLOUR)));
+
+        // The links run between the 8s of each cell of the chain, with the weak link dashed.
+        assert_eq!(count(&svg, "marker-end"), 3);
+        assert_eq!(count(&svg, "stroke-dasharray"), 1);
+        assert!(svg.contains(r#"<line x1="520" y1="360" x2="520" y2="420" stroke="black" stroke-width="1.5" marker-end="url(#arrow)"/>"#));
+        assert!(svg.contains(r#"<line x1="520" y1="420" x2="40" y2="420" stroke="black" stroke-width="1.5" stroke-dasharray="4,3" marker-end="url(#arrow)"/>"#));
+    }
+}
@@ -0,0 +1,62 @@
+//! Hints which reveal the next step of a solve a little at a time.
+
+use grid::{Grid, GridSize};
+use grid::cellset::CellSet;
+use strategies::{Deduction, Step};
+
+/// How much of the next step a hint should reveal.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum HintLevel {
+    /// Only the name of the technique to use.
+    Strategy,
+    /// The name of the technique, along with the regions or cells where it can be found.
+    Location,
+    /// The full description of the step, along with the deductions that it makes.
+    Full,
+}
+
+/// A hint describing the next step that can be taken in a solve.
+pub struct Hint<T: GridSize> {
+    /// The step that the hint describes.
+    pub step: Step<T>,
+    /// The deductions that arise from the step.
+    pub deductions: Vec<Deduction>,
+}
+
+impl<T: GridSize> Hint<T> {
+
+    /// The name of the technique used by the hinted step.
+    pub fn strategy_name(&self) -> String {
+        self.step.get_name()
+    }
+
+    /// The cells which make up the pattern used by the hinted step.
+    pub fn cells(&self, grid: &Grid<T>) -> CellSet<T> {
+        self.step.get_cells(grid)
+    }
+
+    /// The regions in which the pattern used by the hinted step lies.
+    pub fn regions(&self, grid: &Grid<T>) -> Vec<CellSet<T>> {
+        self.step.get_regions(grid)
+    }
+
+    /// Get a readable description of the hint, revealing as much as the given level allows.
+    pub fn get_description(&self, grid: &Grid<T>, level: HintLevel) -> String {
+        match level {
+            HintLevel::Strategy => self.strategy_name(),
+            HintLevel::Location => {
+                let regions = self.regions(grid);
+                if regions.is_empty() {
+                    format!("{} - using cells {}", self.strategy_name(), grid.region_name(&self.cells(grid)))
+                } else {
+                    format!("{} - in {}", self.strategy_name(), regions.iter().map(|x| grid.region_name(x)).collect::<Vec<_>>().join(", "))
+                }
+            },
+            HintLevel::Full => format!(
+                "{} - ({})",
+                self.step.get_description(grid),
+                self.deductions.iter().map(|d| d.get_description(grid)).collect::<Vec<_>>().join(", "),
+            ),
+        }
+    }
+}
@@ -1,16 +1,23 @@
 //! A logical solver for Sudoku
 
+mod hint;
 mod solve_configuration;
+mod verification;
 
 use grid::{Grid, GridSize};
 
-use strategies::{Step, Deduction};
+use strategies::{Step, Deduction, LinkGraph};
 use strategies::Deduction::*;
 
+pub use self::hint::{Hint, HintLevel};
 pub use self::solve_configuration::SolveConfiguration;
+pub use self::verification::{is_sound, UnsoundDeduction};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 /// Represents the result of an attempted solve.
 #[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum SolveResult {
     Solved,
     Contradiction,
@@ -18,6 +25,8 @@ pub enum SolveResult {
 }
 
 /// Stores details about the path taken during a solve.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(bound = ""))]
 pub struct SolveDetails<T: GridSize> {
     /// The result of the solve.
     pub result: SolveResult,
@@ -26,31 +35,73 @@ pub struct SolveDetails<T: GridSize> {
 }
 
 /// Solve, as far as possible, the grid, using the allowed strategies.
+///
+/// If the configuration asks for verification and the puzzle has a unique solution, each
+/// deduction is checked against it, and the solve panics with the details of the first one which
+/// disagrees.
 pub fn solve<T: GridSize>(grid: &mut Grid<T>, config: &SolveConfiguration) -> SolveDetails<T> {
-    let mut steps = Vec::new();
+    let solution = if config.verifies() { verification::unique_solution(grid) } else { None };
+    match solve_against(grid, config, solution.as_deref()) {
+        Ok(details) => details,
+        Err(unsound) => panic!("{}", unsound),
+    }
+}
+
+/// Solve, as far as possible, the grid, using the allowed strategies and checking each deduction
+/// against the unique solution of the puzzle. Nothing is checked if the puzzle doesn't have
+/// exactly one solution.
+pub fn solve_and_verify<T: GridSize>(grid: &mut Grid<T>, config: &SolveConfiguration) -> Result<SolveDetails<T>, Box<UnsoundDeduction<T>>> {
+    let solution = verification::unique_solution(grid);
+    solve_against(grid, config, solution.as_deref())
+}
+
+/// Solve the grid, checking each deduction against the given solution if there is one. The grid
+/// is left as it was before the offending step.
+fn solve_against<T: GridSize>(grid: &mut Grid<T>, config: &SolveConfiguration, solution: Option<&[usize]>) -> Result<SolveDetails<T>, Box<UnsoundDeduction<T>>> {
+    let (mut steps, link_graph) = (Vec::new(), LinkGraph::new());
     while !grid.is_solved() {
-        if let Some((step, deductions)) = find_step(grid, config) {
+        if let Some((step, deductions)) = find_step(grid, config, &link_graph) {
+            if let Some(solution) = solution {
+                if let Some(deduction) = deductions.iter().find(|deduction| !verification::is_sound(deduction, solution)) {
+                    return Err(Box::new(UnsoundDeduction {
+                        step_number: steps.len(),
+                        description: step.get_description(grid),
+                        step,
+                        deduction: deduction.clone(),
+                        grid: grid.clone(),
+                        solution: solution.to_vec(),
+                    }));
+                }
+            }
             for deduction in deductions.clone() {
                 if let Contradiction = deduction {
-                    return SolveDetails { result: SolveResult::Contradiction, steps };
+                    return Ok(SolveDetails { result: SolveResult::Contradiction, steps });
                 } else {
                     grid.apply_deduction(deduction);
                 }
             }
             steps.push((step, deductions));
         } else {
-            return SolveDetails { result: SolveResult::InsufficientStrategies, steps };
+            return Ok(SolveDetails { result: SolveResult::InsufficientStrategies, steps });
         }
     }
 
-    SolveDetails { result: SolveResult::Solved, steps }
+    Ok(SolveDetails { result: SolveResult::Solved, steps })
 }
 
-/// Find the next step using the allowed set of strategies.
-fn find_step<T: GridSize>(grid: &Grid<T>, config: &SolveConfiguration) -> Option<(Step<T>, Vec<Deduction>)> {
+/// Find a hint for the next step of the solve, using the allowed strategies, without applying it
+/// to the grid.
+pub fn hint<T: GridSize>(grid: &Grid<T>, config: &SolveConfiguration) -> Option<Hint<T>> {
+    if grid.is_solved() { return None; }
+    find_step(grid, config, &LinkGraph::new()).map(|(step, deductions)| Hint { step, deductions })
+}
+
+/// Find the next step using the allowed set of strategies, sharing the given links between the
+/// chaining strategies.
+fn find_step<T: GridSize>(grid: &Grid<T>, config: &SolveConfiguration, link_graph: &LinkGraph<T>) -> Option<(Step<T>, Vec<Deduction>)> {
 
     for &strategy in config.strategies() {
-        for step in strategy.find_steps(&grid) {
+        for step in strategy.find_steps_with_links(&grid, link_graph) {
             let deductions = step.get_deductions(grid);
             if deductions.len() > 0 { return Some((step, deductions)); }
         }
@@ -65,7 +116,7 @@ mod tests {
     use std::fs::File;
     use std::io::{BufRead, BufReader};
     use std::path::Path;
-    use grid::variants::classic_from_string;
+    use grid::variants::{classic_from_string, empty_classic};
     use super::*;
 
     fn check_grid<T: GridSize>(grid: &Grid<T>) {
@@ -77,6 +128,62 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_hint_matches_first_step() {
+        let grid = classic_from_string("3.542.81.4879.15.6.29.5637485.793.416132.8957.74.6528.2413.9.655.867.192.965124.8".to_string()).unwrap();
+        let config = SolveConfiguration::with_all_strategies();
+        let hint = hint(&grid, &config).unwrap();
+        let first_step = &solve(&mut grid.clone(), &config).steps[0];
+        assert!(hint.get_description(&grid, HintLevel::Full).starts_with(&first_step.0.get_description(&grid)));
+        assert_eq!(hint.get_description(&grid, HintLevel::Strategy), "Full House");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_solve_details_round_trip() {
+        use grid::variants::Grid9;
+        use serde_json;
+
+        let grid = classic_from_string(".9...4.2...1.8....6.21.....4....57..1..7.9..2..84....5.....65.8....7.6...5.3...4.".to_string()).unwrap();
+        let details = solve(&mut grid.clone(), &SolveConfiguration::with_all_strategies());
+
+        let json = serde_json::to_string(&(&grid, &details)).unwrap();
+        let (parsed_grid, parsed_details): (Grid<Grid9>, SolveDetails<Grid9>) = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(parsed_grid.to_pencilmark_string(), grid.to_pencilmark_string());
+        assert_eq!(parsed_details.result, details.result);
+        for ((step, deductions), (parsed_step, parsed_deductions)) in details.steps.iter().zip(parsed_details.steps.iter()) {
+            assert_eq!(parsed_step.get_description(&parsed_grid), step.get_description(&grid));
+            assert_eq!(parsed_deductions.len(), deductions.len());
+        }
+    }
+
+    #[test]
+    fn test_unsound_deduction_is_reported() {
+        let grid = classic_from_string(".9...4.2...1.8....6.21.....4....57..1..7.9..2..84....5.....65.8....7.6...5.3...4.".to_string()).unwrap();
+        let config = SolveConfiguration::with_all_strategies();
+        let solution = verification::unique_solution(&grid).unwrap();
+        assert!(solve_and_verify(&mut grid.clone(), &config).is_ok());
+
+        // Against a solution with two digits swapped, some deduction must disagree.
+        let mut wrong = solution.clone();
+        let cell = (0..81).find(|&cell| grid.is_empty(cell)).unwrap();
+        let other = (0..81).find(|&other| grid.is_empty(other) && wrong[other] != wrong[cell]).unwrap();
+        wrong.swap(cell, other);
+        let unsound = solve_against(&mut grid.clone(), &config, Some(&wrong)).unwrap_err();
+        assert!(!verification::is_sound(&unsound.deduction, &wrong));
+        assert!(unsound.to_string().contains(&unsound.description));
+        assert!(unsound.to_string().contains(&wrong.iter().map(|digit| digit.to_string()).collect::<String>()));
+    }
+
+    #[test]
+    fn test_unique_solution_needs_exactly_one() {
+        assert!(verification::unique_solution(&empty_classic()).is_none());
+        let solved = "534678912672195348198342567859761423426853791713924856961537284287419635345286179";
+        let grid = classic_from_string(solved.replacen('5', ".", 1)).unwrap();
+        assert_eq!(verification::unique_solution(&grid).unwrap(), solved.bytes().map(|byte| (byte - b'0') as usize).collect::<Vec<_>>());
+    }
+
     #[test]
     fn test_classic_solves() {
         let file = File::open(&Path::new("classic_grids.txt")).unwrap();
@@ -86,7 +193,7 @@ mod tests {
             if !line.is_empty() && !line.starts_with("//") {
                 let mut grid = classic_from_string(line).unwrap();
                 assert_eq!(
-                    solve(&mut grid, &SolveConfiguration::with_all_strategies()).result,
+                    solve(&mut grid, &SolveConfiguration::with_all_strategies().with_verification()).result,
                     SolveResult::Solved
                 );
                 check_grid(&grid);
@@ -3,28 +3,42 @@
 use strategies::{Strategy, ALL_STRATEGIES};
 
 /// Configuration that determines how solving should proceed.
+#[derive(Clone)]
 pub struct SolveConfiguration {
     strategies: Vec<Strategy>,
+    verify: bool,
 }
 
 impl SolveConfiguration {
 
     /// Create a `SolveConfiguration` with all strategies enabled, in default order.
     pub fn with_all_strategies() -> SolveConfiguration {
-        SolveConfiguration { strategies: ALL_STRATEGIES.to_vec() }
+        SolveConfiguration::with_strategies(ALL_STRATEGIES.to_vec())
     }
 
     /// Create a `SolveConfiguration` with all strategies enabled, except the given strategies, in
     /// default order.
     pub fn without_strategies(strategies: Vec<Strategy>) -> SolveConfiguration {
         SolveConfiguration::with_strategies(
-            ALL_STRATEGIES.iter().filter(|&x| !strategies.contains(x)).map(|x| *x).collect()
+            ALL_STRATEGIES.iter().filter(|&x| !strategies.contains(x)).copied().collect()
         )
     }
 
     /// Create a `SolveConfiguration` with the given strategies in the given order.
     pub fn with_strategies(strategies: Vec<Strategy>) -> SolveConfiguration {
-        SolveConfiguration { strategies }
+        SolveConfiguration { strategies, verify: false }
+    }
+
+    /// Check every deduction made while solving against the solution found by brute force, so
+    /// that a faulty strategy makes the solve panic instead of giving a wrong walkthrough.
+    pub fn with_verification(mut self) -> SolveConfiguration {
+        self.verify = true;
+        self
+    }
+
+    /// Whether deductions are checked against the solution while solving.
+    pub fn verifies(&self) -> bool {
+        self.verify
     }
 
     /// Get a slice over the allowed strategies.
@@ -0,0 +1,60 @@
+//! Checking the deductions of a solve against the solution found by brute force.
+
+use generator::brute_force::{BruteForce, BruteForceSolver};
+use grid::{Grid, GridSize};
+use strategies::{Deduction, Step};
+
+use std::fmt;
+
+/// A deduction which disagrees with the solution of the puzzle, together with the step which made
+/// it and the grid the step was found in.
+#[derive(Clone)]
+pub struct UnsoundDeduction<T: GridSize> {
+    /// The number of steps taken before the offending one.
+    pub step_number: usize,
+    /// The step which made the deduction.
+    pub step: Step<T>,
+    /// The description of the step, as it would appear in the walkthrough.
+    pub description: String,
+    /// The deduction which disagrees with the solution.
+    pub deduction: Deduction,
+    /// The grid before the step was applied.
+    pub grid: Grid<T>,
+    /// The solution of the puzzle.
+    pub solution: Vec<usize>,
+}
+
+impl<T: GridSize> fmt::Display for UnsoundDeduction<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Step {} ({}) made an unsound deduction: {}", self.step_number + 1, self.description, self.deduction.get_description(&self.grid))?;
+        let separator = if T::size() > 9 { "," } else { "" };
+        writeln!(f, "Solution: {}", self.solution.iter().map(|digit| digit.to_string()).collect::<Vec<_>>().join(separator))?;
+        write!(f, "{}", self.grid.to_pencilmark_string())
+    }
+}
+
+impl<T: GridSize> fmt::Debug for UnsoundDeduction<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self, f)
+    }
+}
+
+/// The solution of the grid, if it has exactly one.
+pub fn unique_solution<T: GridSize>(grid: &Grid<T>) -> Option<Vec<usize>> {
+    let mut brute_force = BruteForceSolver::for_starting_grid(grid);
+    let mut solutions = brute_force.solutions(&grid.to_clues(), 2);
+    match (solutions.next(), solutions.next()) {
+        (Some(solution), None) => Some(solution),
+        _ => None,
+    }
+}
+
+/// Check whether the deduction agrees with the given solution. A puzzle with a solution can never
+/// be found to be in contradiction.
+pub fn is_sound(deduction: &Deduction, solution: &[usize]) -> bool {
+    match *deduction {
+        Deduction::Placement(cell, value) => solution[cell] == value,
+        Deduction::Elimination(cell, value) => solution[cell] != value,
+        Deduction::Contradiction => false,
+    }
+}
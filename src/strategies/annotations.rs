@@ -0,0 +1,125 @@
+//! Structured data describing the pattern behind a step, for use when drawing the step.
+
+use grid::{Candidate, CellIdx, Grid, GridSize};
+use grid::cellset::CellSet;
+use strategies::{Deduction, Step};
+use strategies::chaining;
+use strategies::xy_chain;
+
+/// The part that a highlighted candidate plays in the pattern behind a step.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum CandidateRole {
+    /// A candidate forming the main body of the pattern.
+    Pattern,
+    /// A fin cell of a finned fish.
+    Fin,
+    /// The pivot cell of a wing.
+    Pivot,
+    /// One of the pincer cells of a wing.
+    Pincer,
+    /// A candidate which is assumed, or deduced, to be true within a chain.
+    On,
+    /// A candidate which is assumed, or deduced, to be false within a chain.
+    Off,
+}
+
+/// A single highlighted candidate in the pattern behind a step.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct AnnotatedCandidate {
+    pub cell: CellIdx,
+    pub value: Candidate,
+    pub role: CandidateRole,
+}
+
+/// A link between two groups of candidates, forming part of a chain.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Link<T: GridSize> {
+    /// The cells at the start of the link.
+    pub from_cells: CellSet<T>,
+    /// The candidate at the start of the link.
+    pub from_value: Candidate,
+    /// The cells at the end of the link.
+    pub to_cells: CellSet<T>,
+    /// The candidate at the end of the link.
+    pub to_value: Candidate,
+    /// Whether the link is strong (the start being false forces the end to be true) or weak (the
+    /// start being true forces the end to be false).
+    pub strong: bool,
+}
+
+/// A structured description of a step, listing everything that should be highlighted when the
+/// step is displayed.
+#[derive(Debug)]
+pub struct StepAnnotations<T: GridSize> {
+    /// The cells which make up the pattern.
+    pub cells: CellSet<T>,
+    /// The candidates which make up the pattern, split by the role they play.
+    pub candidates: Vec<AnnotatedCandidate>,
+    /// The strong and weak links between candidates, for chaining patterns.
+    pub links: Vec<Link<T>>,
+    /// The candidates which are placed or eliminated by the step.
+    pub deductions: Vec<Deduction>,
+}
+
+/// Build up the annotations for the given step.
+pub fn get_annotations<T: GridSize>(grid: &Grid<T>, step: &Step<T>) -> StepAnnotations<T> {
+
+    let (candidates, links) = match step {
+        Step::NoCandidatesForCell { .. } | Step::NoPlaceForCandidateInRegion { .. } => (vec![], vec![]),
+        Step::FullHouse { cell, value, .. } | Step::HiddenSingle { cell, value, .. } | Step::NakedSingle { cell, value } => {
+            (vec![AnnotatedCandidate { cell: *cell, value: *value, role: CandidateRole::Pattern }], vec![])
+        },
+        Step::BoxLine { region, value, .. } => {
+            (with_value(grid, &grid.cells_with_candidate_in_region(*value, region), *value, CandidateRole::Pattern), vec![])
+        },
+        Step::HiddenSubset { cells, values, .. } | Step::NakedSubset { cells, values, .. } => {
+            let mut candidates = Vec::new();
+            for value in values.iter() {
+                candidates.append(&mut with_value(grid, cells, value, CandidateRole::Pattern));
+            }
+            (candidates, vec![])
+        },
+        Step::Fish { base, value, .. } => (with_value(grid, base, *value, CandidateRole::Pattern), vec![]),
+        Step::FinnedFish { base, fins, value, .. } => {
+            let mut candidates = with_value(grid, &(base & !fins), *value, CandidateRole::Pattern);
+            candidates.append(&mut with_value(grid, fins, *value, CandidateRole::Fin));
+            (candidates, vec![])
+        },
+        Step::XYWing { pivot, pincer1, pincer2, .. } | Step::XYZWing { pivot, pincer1, pincer2, .. } => {
+            let mut candidates = all_candidates(grid, &CellSet::from_cell(*pivot), CandidateRole::Pivot);
+            candidates.append(&mut all_candidates(grid, &CellSet::from_cells(vec![*pincer1, *pincer2]), CandidateRole::Pincer));
+            (candidates, vec![])
+        },
+        Step::WWing { pincer1, pincer2, region, covered_value, .. } => {
+            let mut candidates = all_candidates(grid, &CellSet::from_cells(vec![*pincer1, *pincer2]), CandidateRole::Pincer);
+            candidates.append(&mut with_value(grid, &grid.cells_with_candidate_in_region(*covered_value, region), *covered_value, CandidateRole::Pattern));
+            (candidates, vec![])
+        },
+        Step::WXYZWing { cells, .. } => (all_candidates(grid, cells, CandidateRole::Pattern), vec![]),
+        Step::XChain { chain } | Step::Aic { chain } | Step::AlsAic { chain } => chaining::get_aic_annotations(chain),
+        Step::XYChain { .. } => xy_chain::get_annotations(step),
+        Step::ForcingChain { chain } | Step::AlsForcingChain { chain } => chaining::get_forcing_chain_annotations(chain),
+        Step::Msls { base, digits, .. } => {
+            let cells = grid.empty_cells_in_region(&CellSet::union(base));
+            let mut candidates = Vec::new();
+            for value in digits.iter() {
+                candidates.append(&mut with_value(grid, &cells, value, CandidateRole::Pattern));
+            }
+            (candidates, vec![])
+        },
+    };
+
+    StepAnnotations { cells: step.get_cells(grid), candidates, links, deductions: step.get_deductions(grid) }
+}
+
+/// Annotate every occurrence of the given value within the given cells.
+fn with_value<T: GridSize>(grid: &Grid<T>, cells: &CellSet<T>, value: Candidate, role: CandidateRole) -> Vec<AnnotatedCandidate> {
+    grid.cells_with_candidate_in_region(value, cells).map(|cell| AnnotatedCandidate { cell, value, role })
+}
+
+/// Annotate every candidate within the given cells.
+fn all_candidates<T: GridSize>(grid: &Grid<T>, cells: &CellSet<T>, role: CandidateRole) -> Vec<AnnotatedCandidate> {
+    cells.iter()
+        .flat_map(|cell| grid.candidates(cell).map(|value| AnnotatedCandidate { cell, value, role }))
+        .collect()
+}
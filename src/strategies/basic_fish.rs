@@ -47,9 +47,9 @@ fn find_fish<'a, T: GridSize>(grid: &'a Grid<T>, degree: usize, value: usize, ba
             // If we have exactly as many cover sets as base sets, then we might have some fishy
             // eliminations on our hands.
             if cover_sets.len() == degree {
-                let cover_union = CellSet::union(&cover_sets) & &candidate_positions;
-                if !(&cover_union & !&base_union).is_empty() {
-                    yield Step::Fish { degree, base_type, base: base_union.clone(), cover: cover_union.clone(), value };
+                let cover_union = CellSet::union(&cover_sets) & candidate_positions;
+                if !(cover_union & !&base_union).is_empty() {
+                    yield Step::Fish { degree, base_type, base: base_union, cover: cover_union, value };
                 }
             }
         }
@@ -83,7 +83,7 @@ pub fn get_description<T: GridSize>(grid: &Grid<T>, fish: &Step<T>) -> String {
     }
 }
 
-fn get_fish_name<'a>(size: usize) -> &'a str {
+pub fn get_fish_name<'a>(size: usize) -> &'a str {
     match size {
         2 => "X-Wing",
         3 => "Swordfish",
@@ -92,14 +92,14 @@ fn get_fish_name<'a>(size: usize) -> &'a str {
     }
 }
 
-fn get_base_regions<T: GridSize>(grid: &Grid<T>, base_type: RowOrColumn, base_union: &CellSet<T>) -> Vec<CellSet<T>> {
+pub fn get_base_regions<T: GridSize>(grid: &Grid<T>, base_type: RowOrColumn, base_union: &CellSet<T>) -> Vec<CellSet<T>> {
     match base_type {
         Row => grid.intersecting_rows(base_union),
         Column => grid.intersecting_columns(base_union),
     }
 }
 
-fn get_cover_regions<T: GridSize>(grid: &Grid<T>, base_type: RowOrColumn, cover_union: &CellSet<T>) -> Vec<CellSet<T>> {
+pub fn get_cover_regions<T: GridSize>(grid: &Grid<T>, base_type: RowOrColumn, cover_union: &CellSet<T>) -> Vec<CellSet<T>> {
     match base_type {
         Row => grid.intersecting_columns(cover_union),
         Column => grid.intersecting_rows(cover_union),
@@ -22,7 +22,7 @@ pub fn find<'a, T: GridSize>(grid: &'a Grid<T>) -> impl Iterator<Item = Step<T>>
                 let common_neighbours = grid.common_neighbours(&cells);
                 let elimination_cells = grid.cells_with_candidate_in_region(val, &common_neighbours);
                 if !elimination_cells.is_empty() {
-                    yield Step::BoxLine { region: region.clone(), neighbours: elimination_cells.clone(), value: val };
+                    yield Step::BoxLine { region: *region, neighbours: elimination_cells, value: val };
                 }
             }
         }
@@ -11,13 +11,18 @@
 use grid::{Candidate, CellIdx, Grid, GridSize};
 use grid::cellset::CellSet;
 use strategies::Deduction;
+use strategies::annotations::{AnnotatedCandidate, Link};
+use strategies::chaining::graph::Links;
 use strategies::chaining::nodes;
 use strategies::chaining::nodes::ChainNode;
 
 use std::collections::{HashSet, VecDeque};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
-#[derive(PartialEq, Eq)]
 /// A struct representing a single inference which is part of an AIC
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(bound = ""))]
 pub struct AicInference<T: GridSize> {
     node: ChainNode<T>,
     negated: bool,
@@ -40,26 +45,13 @@ impl <T: GridSize> AicInference<T> {
     }
 }
 
-/// Search for AICs in the given grid, constructed from the given possible nodes.
-pub fn find_aics<T: GridSize>(grid: &Grid<T>, nodes: Vec<ChainNode<T>>) -> Vec<Aic<T>> {
-
-    // Create the adjacency lists for the given nodes. Each node is treated as two different
-    // vertices in the graph of linked inferences - one for the inference where the node is ON,
-    // and one for the inference where the node is OFF.
-    let mut adjacencies = vec![vec![]; 2 * nodes.len()];
-    for (start_idx, start_node) in nodes.iter().enumerate() {
-        for (end_idx, end_node) in nodes.iter().enumerate() {
-            if (start_idx != end_idx) && nodes::is_linked_on_to_off(grid, start_node, end_node) {
-                adjacencies[2 * start_idx].push(2 * end_idx + 1);
-            }
-            if (start_idx != end_idx) && nodes::is_linked_off_to_on(grid, start_node, end_node) {
-                adjacencies[2 * start_idx + 1].push(2 * end_idx);
-            }
-        }
-    }
+/// Search for AICs in the given grid, constructed from the given nodes and the links between them.
+pub fn find_aics<T: GridSize>(grid: &Grid<T>, links: &Links<T>) -> Vec<Aic<T>> {
+
+    let (nodes, adjacencies) = (&links.nodes, &links.adjacencies);
 
     // For each node, decide which candidates would be eliminated if it were ON
-    let affected_candidates: Vec<_> = nodes.iter().map(|node| find_affected_candidates(grid, node)).collect();
+    let affected_candidates: Vec<_> = nodes.iter().map(|node| affected_cells_by_value(grid, node)).collect();
 
     // For each OFF version of a node, perform a breadth-first search and look for ON nodes that
     // are a result, via an AIC, of the original OFF inference. If the OFF inference and the linked
@@ -67,7 +59,7 @@ pub fn find_aics<T: GridSize>(grid: &Grid<T>, nodes: Vec<ChainNode<T>>) -> Vec<A
     // of the chain.
     let mut chains = Vec::new();
     for start_idx in 0..nodes.len() {
-        for chain in breadth_first_search(&nodes, &adjacencies, &affected_candidates, start_idx) {
+        for chain in breadth_first_search(nodes, adjacencies, &affected_candidates, start_idx) {
             chains.push(chain);
         }
     }
@@ -92,13 +84,23 @@ pub fn get_aic_description<T: GridSize>(grid: &Grid<T>, aic: &Aic<T>) -> String
     description
 }
 
+/// Get all of the cells which appear in the given chain.
+pub fn get_aic_cells<T: GridSize>(aic: &Aic<T>) -> CellSet<T> {
+    aic.iter().fold(CellSet::empty(), |acc, inference| acc | inference.node.get_cells())
+}
+
+/// Get the highlighted candidates and links which make up the given chain.
+pub fn get_aic_annotations<T: GridSize>(aic: &Aic<T>) -> (Vec<AnnotatedCandidate>, Vec<Link<T>>) {
+    nodes::annotate_chain(aic.iter().map(|inference| (&inference.node, inference.negated)))
+}
+
 /// Find candidates which would be eliminated as a result of the given node being ON
 fn find_affected_candidates<T: GridSize>(grid: &Grid<T>, node: &ChainNode<T>) -> HashSet<AffectedCandidate> {
 
     let mut affected_candidates = HashSet::new();
 
     // All cells which are in sight of every candidate that might be switched ON in this node are dead
-    let (value, value_cells) = (get_value(node), get_value_cells(node));
+    let (value, value_cells) = (node.get_value(), node.get_value_cells());
     let common_neighbours = CellSet::intersection(&value_cells.map(|ix| grid.neighbours(ix)));
     for cell in grid.cells_with_candidate_in_region(value, &common_neighbours).iter() {
         affected_candidates.insert((cell, value));
@@ -117,6 +119,21 @@ fn find_affected_candidates<T: GridSize>(grid: &Grid<T>, node: &ChainNode<T>) ->
     affected_candidates
 }
 
+/// Find the cells from which each value would be eliminated as a result of the given node being ON,
+/// indexed by value - 1.
+fn affected_cells_by_value<T: GridSize>(grid: &Grid<T>, node: &ChainNode<T>) -> Vec<CellSet<T>> {
+    let mut affected_cells = vec![CellSet::empty(); T::size()];
+    for (cell, value) in find_affected_candidates(grid, node) {
+        affected_cells[value - 1].add_cell(cell);
+    }
+    affected_cells
+}
+
+/// Determine whether two nodes have no affected candidates in common.
+fn are_disjoint<T: GridSize>(first: &[CellSet<T>], second: &[CellSet<T>]) -> bool {
+    first.iter().zip(second).all(|(first_cells, second_cells)| (first_cells & second_cells).is_empty())
+}
+
 /// Get the deductions that arise from a strong link between the two given nodes.
 fn get_strong_link_deductions<T: GridSize>(grid: &Grid<T>, node1: &ChainNode<T>, node2: &ChainNode<T>) -> Vec<Deduction> {
     let first_affected_candidates = find_affected_candidates(grid, node1);
@@ -125,27 +142,9 @@ fn get_strong_link_deductions<T: GridSize>(grid: &Grid<T>, node1: &ChainNode<T>,
     common_affected_candidates.map(|&(cell, value)| Deduction::Elimination(cell, value)).collect()
 }
 
-/// Extract the value from a `ChainNode`
-fn get_value<T: GridSize>(node: &ChainNode<T>) -> Candidate {
-    match node {
-        ChainNode::Value { value, .. } => *value,
-        ChainNode::Group { value, .. } => *value,
-        ChainNode::Als { value, .. } => *value,
-    }
-}
-
-/// Extract the cells with the right value from a `ChainNode`
-fn get_value_cells<T: GridSize>(node: &ChainNode<T>) -> CellSet<T> {
-    match node {
-        ChainNode::Value { cell, .. } => CellSet::from_cell(*cell),
-        ChainNode::Group { cells, .. } => cells.clone(),
-        ChainNode::Als { cells_with_value, .. } => cells_with_value.clone(),
-    }
-}
-
 /// Perform a breadth-first search looking for chains starting from the OFF version of the given node
 /// and ending in the ON version of another node, in such a way that eliminations result.
-fn breadth_first_search<T: GridSize>(nodes: &[ChainNode<T>], adjacencies: &[Vec<usize>], affected_candidates: &[HashSet<AffectedCandidate>], start_idx: usize) -> Vec<Aic<T>> {
+fn breadth_first_search<T: GridSize>(nodes: &[ChainNode<T>], adjacencies: &[Vec<usize>], affected_candidates: &[Vec<CellSet<T>>], start_idx: usize) -> Vec<Aic<T>> {
 
     // The current state of the search, and a record of how each node was reached
     let (mut queue, mut visited, mut parents) = (VecDeque::new(), vec![false; adjacencies.len()], vec![0; adjacencies.len()]);
@@ -157,7 +156,7 @@ fn breadth_first_search<T: GridSize>(nodes: &[ChainNode<T>], adjacencies: &[Vec<
         let current_idx = queue.pop_front().unwrap();
 
         // If we have a usable chain, then store it
-        if current_idx % 2 == 0 && !affected_candidates[current_idx / 2].is_disjoint(&affected_candidates[start_idx]) {
+        if current_idx % 2 == 0 && !are_disjoint(&affected_candidates[current_idx / 2], &affected_candidates[start_idx]) {
             chains.push(create_chain(nodes, &parents, 2 * start_idx + 1, current_idx));
         }
 
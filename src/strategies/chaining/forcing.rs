@@ -9,14 +9,20 @@
 //! consequence must indeed be true, and can be added to the grid.
 
 use grid::{Grid, GridSize};
+use grid::cellset::CellSet;
 use strategies::Deduction;
+use strategies::annotations::{AnnotatedCandidate, Link};
+use strategies::chaining::graph::Links;
 use strategies::chaining::nodes;
 use strategies::chaining::nodes::ChainNode;
 
 use std::collections::VecDeque;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
-#[derive(PartialEq, Eq)]
 /// A struct representing a single inference which is part of a forcing chain
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(bound = ""))]
 pub struct ForcingChainInference<T: GridSize> {
     node: ChainNode<T>,
     negated: bool,
@@ -36,25 +42,12 @@ impl <T: GridSize> ForcingChainInference<T> {
     }
 }
 
-/// Search for forcing chains in the given grid, using the given nodes as possible links in a chain.
-pub fn find_forcing_chains<T: GridSize>(grid: &Grid<T>, nodes: Vec<ChainNode<T>>) -> Vec<ForcingChain<T>> {
+/// Search for forcing chains in the given grid, using the given nodes and the links between them.
+pub fn find_forcing_chains<T: GridSize>(grid: &Grid<T>, links: &Links<T>) -> Vec<ForcingChain<T>> {
 
     let mut chains = Vec::new();
 
-    // Create the adjacency lists for the given nodes. Each node is treated as two different
-    // vertices in the graph of linked inferences - one for the inference where the node is ON,
-    // and one for the inference where the node is OFF.
-    let mut adjacencies = vec![vec![]; 2 * nodes.len()];
-    for (start_idx, start_node) in nodes.iter().enumerate() {
-        for (end_idx, end_node) in nodes.iter().enumerate() {
-            if (start_idx != end_idx) && nodes::is_linked_on_to_off(grid, start_node, end_node) {
-                adjacencies[2 * start_idx].push(2 * end_idx + 1);
-            }
-            if (start_idx != end_idx) && nodes::is_linked_off_to_on(grid, start_node, end_node) {
-                adjacencies[2 * start_idx + 1].push(2 * end_idx);
-            }
-        }
-    }
+    let (nodes, adjacencies) = (&links.nodes, &links.adjacencies);
 
     // Determine which nodes represent single candidates, and store the indices of these nodes.
     let mut nodes_by_cell_and_candidate = vec![vec![0; T::size() + 1]; T::size() * T::size()];
@@ -68,7 +61,7 @@ pub fn find_forcing_chains<T: GridSize>(grid: &Grid<T>, nodes: Vec<ChainNode<T>>
     let mut search_results = vec![SearchResults { visited: Vec::new(), parents: Vec::new() }; nodes.len()];
     for (idx, node) in nodes.iter().enumerate() {
         if let ChainNode::Value { .. } = node {
-            search_results[idx] = breadth_first_search(&adjacencies, idx);
+            search_results[idx] = breadth_first_search(adjacencies, idx);
         }
     }
 
@@ -76,7 +69,7 @@ pub fn find_forcing_chains<T: GridSize>(grid: &Grid<T>, nodes: Vec<ChainNode<T>>
     // that cell.
     for cell in grid.empty_cells().iter().filter(|&c| grid.num_candidates(c) >= 3) {
         let premises = grid.candidates(cell).map(|value| nodes_by_cell_and_candidate[cell][value]);
-        chains.append(&mut find_chains(&nodes, &adjacencies, &search_results, &premises));
+        chains.append(&mut find_chains(nodes, adjacencies, &search_results, &premises));
     }
 
     // For each region, and each candidate missing from the region, determine the common consequences
@@ -86,7 +79,7 @@ pub fn find_forcing_chains<T: GridSize>(grid: &Grid<T>, nodes: Vec<ChainNode<T>>
             let cells = grid.cells_with_candidate_in_region(candidate, region);
             if cells.len() >= 3 {
                 let premises = cells.map(|cell| nodes_by_cell_and_candidate[cell][candidate]);
-                chains.append(&mut find_chains(&nodes, &adjacencies, &search_results, &premises));
+                chains.append(&mut find_chains(nodes, adjacencies, &search_results, &premises));
             }
         }
     }
@@ -135,6 +128,24 @@ pub fn get_forcing_chain_description<T: GridSize>(grid: &Grid<T>, forcing_chain:
     description
 }
 
+/// Get all of the cells which appear in any branch of the given chain.
+pub fn get_forcing_chain_cells<T: GridSize>(forcing_chain: &ForcingChain<T>) -> CellSet<T> {
+    forcing_chain.iter()
+        .flat_map(|chain| chain.iter())
+        .fold(CellSet::empty(), |acc, inference| acc | inference.node.get_cells())
+}
+
+/// Get the highlighted candidates and links which make up every branch of the given chain.
+pub fn get_forcing_chain_annotations<T: GridSize>(forcing_chain: &ForcingChain<T>) -> (Vec<AnnotatedCandidate>, Vec<Link<T>>) {
+    let (mut candidates, mut links) = (Vec::new(), Vec::new());
+    for chain in forcing_chain {
+        let (mut chain_candidates, mut chain_links) = nodes::annotate_chain(chain.iter().map(|inference| (&inference.node, inference.negated)));
+        candidates.append(&mut chain_candidates);
+        links.append(&mut chain_links);
+    }
+    (candidates, links)
+}
+
 /// Determine the common consequences, and chains proving them, for the given set of starting premises.
 fn find_chains<T: GridSize>(nodes: &[ChainNode<T>], adjacencies: &[Vec<usize>], search_information: &[SearchResults], premises: &[usize]) -> Vec<ForcingChain<T>> {
 
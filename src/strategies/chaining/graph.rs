@@ -0,0 +1,303 @@
+//! A graph of the links between chain nodes, which can be shared between the chaining strategies
+//! and kept up to date as the grid is solved.
+//!
+//! Links between nodes with different values only arise between nodes which share cells, so each
+//! node is only checked against those with the same value and those in the same cells. Links
+//! between nodes of one value depend only on the candidates for that value, and links between
+//! nodes of different values only on the cells involved. After deductions, only links touching a
+//! changed value or a changed cell need to be worked out again.
+
+use grid::{Candidate, Grid, GridSize};
+use grid::candidateset::CandidateSet;
+use grid::cellset::CellSet;
+use strategies::chaining::nodes;
+use strategies::chaining::nodes::ChainNode;
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+/// The nodes that a chain search is built from, along with the links between them.
+///
+/// Each node is treated as two different vertices in the graph of linked inferences - `2 * idx`
+/// for the inference where the node is ON, and `2 * idx + 1` for the inference where it is OFF.
+/// The adjacency list of each vertex is sorted.
+#[derive(Clone)]
+pub struct Links<T: GridSize> {
+    pub nodes: Vec<ChainNode<T>>,
+    pub adjacencies: Vec<Vec<usize>>,
+}
+
+impl<T: GridSize> Links<T> {
+
+    /// Work out the links between the `Value` and `Group` nodes of the grid, and also the `Als`
+    /// nodes if asked for, from scratch.
+    pub fn for_grid(grid: &Grid<T>, include_als: bool) -> Links<T> {
+        update(grid, include_als, None).links
+    }
+
+    /// Work out the links between the given nodes from scratch.
+    pub fn between(grid: &Grid<T>, nodes: Vec<ChainNode<T>>) -> Links<T> {
+        let adjacencies = find_adjacencies(grid, &nodes, None, CellSet::empty(), CandidateSet::empty());
+        Links { nodes, adjacencies }
+    }
+
+    /// Restrict the links to the `Value` and `Group` nodes for a single candidate.
+    pub fn for_candidate(&self, candidate: Candidate) -> Links<T> {
+        let kept: Vec<usize> = self.nodes.iter().enumerate()
+            .filter(|(_, node)| match node {
+                ChainNode::Value { value, .. } | ChainNode::Group { value, .. } => *value == candidate,
+                ChainNode::Als { .. } => false,
+            })
+            .map(|(idx, _)| idx)
+            .collect();
+        self.restricted(&kept)
+    }
+
+    /// Restrict the links to the nodes with the given indices, which must be in increasing order.
+    fn restricted(&self, kept: &[usize]) -> Links<T> {
+        let mut new_indices = vec![None; self.nodes.len()];
+        for (new_idx, &idx) in kept.iter().enumerate() {
+            new_indices[idx] = Some(new_idx);
+        }
+        let adjacencies = kept.iter()
+            .flat_map(|&idx| vec![2 * idx, 2 * idx + 1])
+            .map(|vertex| self.adjacencies[vertex].iter().filter_map(|&next| new_indices[next / 2].map(|idx| 2 * idx + next % 2)).collect())
+            .collect();
+        Links { nodes: kept.iter().map(|&idx| self.nodes[idx].clone()).collect(), adjacencies }
+    }
+}
+
+/// The links between the chain nodes of a grid, to be shared between the chaining strategies.
+///
+/// The links are worked out the first time they are asked for. Afterwards, only those which may
+/// have been affected by changes to the grid are worked out again, so a `LinkGraph` should only
+/// be used with a single grid as it is solved.
+pub struct LinkGraph<T: GridSize> {
+    state: RefCell<Option<GraphState<T>>>,
+}
+
+/// The links as they were last worked out.
+struct GraphState<T: GridSize> {
+
+    /// The candidates of each cell at the time
+    candidates: Vec<CandidateSet<T>>,
+
+    /// The number of `Value` and `Group` nodes, which come before any `Als` nodes
+    num_basic_nodes: usize,
+
+    /// Whether `Als` nodes were included. Once they have been asked for they are kept up to date.
+    includes_als: bool,
+
+    links: Links<T>,
+}
+
+impl<T: GridSize> Default for LinkGraph<T> {
+    fn default() -> LinkGraph<T> {
+        LinkGraph::new()
+    }
+}
+
+impl<T: GridSize> LinkGraph<T> {
+
+    /// Create a `LinkGraph` which has not yet worked out any links.
+    pub fn new() -> LinkGraph<T> {
+        LinkGraph { state: RefCell::new(None) }
+    }
+
+    /// Get the links between the `Value` and `Group` nodes of the grid, and also the `Als` nodes
+    /// if asked for.
+    pub fn links(&self, grid: &Grid<T>, include_als: bool) -> Links<T> {
+        let mut state = self.state.borrow_mut();
+        let previous = state.take();
+        let keep_als = include_als || previous.as_ref().is_some_and(|previous| previous.includes_als);
+        let updated = update(grid, keep_als, previous);
+        let links = if include_als || !updated.includes_als {
+            updated.links.clone()
+        } else {
+            updated.links.restricted(&(0..updated.num_basic_nodes).collect::<Vec<_>>())
+        };
+        *state = Some(updated);
+        links
+    }
+}
+
+/// Work out the links in the grid, reusing those from before that can't have changed.
+fn update<T: GridSize>(grid: &Grid<T>, include_als: bool, previous: Option<GraphState<T>>) -> GraphState<T> {
+
+    // Find which cells and values have changed since the links were last worked out
+    let candidates: Vec<CandidateSet<T>> = grid.cells().map(|cell| grid.candidates(cell));
+    let (mut changed_cells, mut changed_values) = (CellSet::empty(), CandidateSet::empty());
+    if let Some(ref previous) = previous {
+        for (cell, (old, new)) in previous.candidates.iter().zip(&candidates).enumerate() {
+            if old != new {
+                changed_cells.add_cell(cell);
+                changed_values |= *old ^ *new;
+            }
+        }
+    }
+    if changed_cells.is_empty() && previous.as_ref().is_some_and(|previous| previous.includes_als == include_als) {
+        return previous.unwrap();
+    }
+
+    // Create the possible chain nodes
+    let mut nodes = nodes::get_value_nodes(grid);
+    nodes.append(&mut nodes::get_group_nodes(grid));
+    let num_basic_nodes = nodes.len();
+    if include_als {
+        nodes.append(&mut nodes::get_als_nodes(grid));
+    }
+
+    let adjacencies = find_adjacencies(grid, &nodes, previous.as_ref().map(|previous| &previous.links), changed_cells, changed_values);
+    GraphState { candidates, num_basic_nodes, includes_als: include_als, links: Links { nodes, adjacencies } }
+}
+
+/// Work out the links between the given nodes. Links from before are reused unless they involve
+/// a changed value or a changed cell.
+fn find_adjacencies<T: GridSize>(grid: &Grid<T>, nodes: &[ChainNode<T>], previous_links: Option<&Links<T>>, changed_cells: CellSet<T>, changed_values: CandidateSet<T>) -> Vec<Vec<usize>> {
+
+    // Index the nodes by value, by single cell and by the cells of an ALS, which between them
+    // contain every node that another node can be linked to
+    let mut by_value = vec![Vec::new(); T::size() + 1];
+    let mut by_cell = vec![Vec::new(); T::size() * T::size()];
+    let mut by_als_cells = HashMap::new();
+    for (idx, node) in nodes.iter().enumerate() {
+        by_value[node.get_value()].push(idx);
+        let value_cells = node.get_value_cells();
+        if value_cells.len() == 1 {
+            by_cell[value_cells.first().unwrap()].push(idx);
+        }
+        if let ChainNode::Als { cells, .. } = node {
+            by_als_cells.entry(*cells).or_insert_with(Vec::new).push(idx);
+        }
+    }
+
+    // Find where each node was before, if it existed, and where each node from before is now
+    let (mut previous_indices, mut current_indices) = (vec![None; nodes.len()], Vec::new());
+    if let Some(previous_links) = previous_links {
+        let indices: HashMap<&ChainNode<T>, usize> = nodes.iter().enumerate().map(|(idx, node)| (node, idx)).collect();
+        current_indices = previous_links.nodes.iter().map(|node| indices.get(node).cloned()).collect();
+        for (old_idx, idx) in current_indices.iter().enumerate() {
+            if let Some(idx) = *idx {
+                previous_indices[idx] = Some(old_idx);
+            }
+        }
+    }
+    let mut new_by_value = vec![Vec::new(); T::size() + 1];
+    for (idx, node) in nodes.iter().enumerate().filter(|&(idx, _)| previous_indices[idx].is_none()) {
+        new_by_value[node.get_value()].push(idx);
+    }
+
+    // Whether the link between two nodes from before might have changed
+    let affected = |start_node: &ChainNode<T>, end_node: &ChainNode<T>| if start_node.get_value() == end_node.get_value() {
+        changed_values.has_candidate(start_node.get_value())
+    } else {
+        !(changed_cells & (start_node.get_cells() | end_node.get_cells())).is_empty()
+    };
+
+    let mut adjacencies = vec![vec![]; 2 * nodes.len()];
+    for (start_idx, start_node) in nodes.iter().enumerate() {
+
+        let value = start_node.get_value();
+        let mut partners = match (previous_links, previous_indices[start_idx]) {
+
+            // Keep the links from before which can't have changed, and only work out the rest
+            (Some(previous_links), Some(old_start_idx)) => {
+                for vertex in 0..2 {
+                    for &old_end_vertex in &previous_links.adjacencies[2 * old_start_idx + vertex] {
+                        if let Some(end_idx) = current_indices[old_end_vertex / 2] {
+                            if !affected(start_node, &nodes[end_idx]) {
+                                adjacencies[2 * start_idx + vertex].push(2 * end_idx + old_end_vertex % 2);
+                            }
+                        }
+                    }
+                }
+                if changed_values.has_candidate(value) { by_value[value].clone() } else { new_by_value[value].clone() }
+            },
+
+            _ => by_value[value].clone(),
+        };
+
+        let value_cells = start_node.get_value_cells();
+        if value_cells.len() == 1 {
+            partners.extend(by_cell[value_cells.first().unwrap()].iter().filter(|&&end_idx| nodes[end_idx].get_value() != value));
+        }
+        if let ChainNode::Als { cells, .. } = start_node {
+            partners.extend(by_als_cells[cells].iter().filter(|&&end_idx| nodes[end_idx].get_value() != value));
+        }
+        partners.sort_unstable();
+        partners.dedup();
+
+        let needs_checking = |end_idx: usize| end_idx != start_idx && match (previous_indices[start_idx], previous_indices[end_idx]) {
+            (Some(_), Some(_)) => affected(start_node, &nodes[end_idx]),
+            _ => true,
+        };
+        for end_idx in partners.into_iter().filter(|&end_idx| needs_checking(end_idx)) {
+            if nodes::is_linked_on_to_off(grid, start_node, &nodes[end_idx]) {
+                adjacencies[2 * start_idx].push(2 * end_idx + 1);
+            }
+            if nodes::is_linked_off_to_on(grid, start_node, &nodes[end_idx]) {
+                adjacencies[2 * start_idx + 1].push(2 * end_idx);
+            }
+        }
+        adjacencies[2 * start_idx].sort_unstable();
+        adjacencies[2 * start_idx + 1].sort_unstable();
+    }
+
+    adjacencies
+}
+
+#[cfg(test)]
+mod tests {
+
+    use grid::variants::*;
+    use solver::{solve, SolveConfiguration};
+    use super::*;
+
+    /// Work out the links by checking every pair of nodes.
+    fn all_links<T: GridSize>(grid: &Grid<T>, nodes: &[ChainNode<T>]) -> Vec<Vec<usize>> {
+        let mut adjacencies = vec![vec![]; 2 * nodes.len()];
+        for (start_idx, start_node) in nodes.iter().enumerate() {
+            for (end_idx, end_node) in nodes.iter().enumerate() {
+                if (start_idx != end_idx) && nodes::is_linked_on_to_off(grid, start_node, end_node) {
+                    adjacencies[2 * start_idx].push(2 * end_idx + 1);
+                }
+                if (start_idx != end_idx) && nodes::is_linked_off_to_on(grid, start_node, end_node) {
+                    adjacencies[2 * start_idx + 1].push(2 * end_idx);
+                }
+            }
+        }
+        adjacencies
+    }
+
+    fn check_links<T: GridSize>(puzzle: Grid<T>) {
+        let config = SolveConfiguration::with_all_strategies();
+        let (graph, mut grid) = (LinkGraph::new(), puzzle.clone());
+        for (step_number, (_, deductions)) in solve(&mut puzzle.clone(), &config).steps.into_iter().enumerate() {
+
+            // Leave the ALS nodes out to begin with, to check that they can be added later. The
+            // links are kept up to date at every step, but only checked now and then.
+            let include_als = step_number >= 3;
+            let links = graph.links(&grid, include_als);
+            if step_number % 8 == 0 {
+                assert_eq!(links.nodes, LinkGraph::new().links(&grid, include_als).nodes);
+                assert_eq!(links.adjacencies, all_links(&grid, &links.nodes));
+                let basic_links = graph.links(&grid, false);
+                assert_eq!(basic_links.adjacencies, all_links(&grid, &basic_links.nodes));
+                for candidate in grid.values() {
+                    let (shared, separate) = (basic_links.for_candidate(candidate), Links::between(&grid, nodes::get_nodes_for_candidate(&grid, candidate)));
+                    assert_eq!((shared.nodes, shared.adjacencies), (separate.nodes, separate.adjacencies));
+                }
+            }
+
+            for deduction in deductions {
+                grid.apply_deduction(deduction);
+            }
+        }
+    }
+
+    #[test]
+    fn test_incremental_links() {
+        check_links(classic_from_string(".8..7.5...9......7.5.1.6..8.4.3..6....8...9....6..9.2.4..7.5.6.8......3...2.4..7.".to_string()).unwrap());
+        check_links(windoku_from_string("......79...97..1..7..6.....4.......9....9....5.......4.....6..8..7..43...21......".to_string()).unwrap());
+    }
+}
@@ -2,91 +2,88 @@
 
 mod aic;
 mod forcing;
+mod graph;
 mod nodes;
 
 use grid::{Grid, GridSize};
 use strategies::Step;
+use strategies::chaining::graph::Links;
 use utils::GeneratorAdapter;
 
-pub use self::aic::{Aic, get_aic_deductions, get_aic_description};
-pub use self::forcing::{ForcingChain, get_forcing_chain_deductions, get_forcing_chain_description};
+pub use self::aic::{Aic, get_aic_annotations, get_aic_cells, get_aic_deductions, get_aic_description};
+pub use self::forcing::{ForcingChain, get_forcing_chain_annotations, get_forcing_chain_cells, get_forcing_chain_deductions, get_forcing_chain_description};
+pub use self::graph::LinkGraph;
 
-pub fn find_xchains<'a, T: GridSize>(grid: &'a Grid<T>) -> impl Iterator<Item = Step<T>> + 'a {
+pub fn find_xchains<'a, T: GridSize>(grid: &'a Grid<T>, link_graph: Option<&'a LinkGraph<T>>) -> impl Iterator<Item = Step<T>> + 'a {
 
     GeneratorAdapter::of(#[coroutine] move || {
 
+        // Without a shared graph, only the links for each candidate in turn need working out
+        let shared_links = link_graph.map(|link_graph| link_graph.links(grid, false));
         for candidate in grid.values().into_iter() {
 
-            // Create the possible chain nodes for this candidate
-            let mut nodes = nodes::get_value_nodes_for_candidate(grid, candidate);
-            nodes.append(&mut nodes::get_group_nodes_for_candidate(grid, candidate));
+            // Find the links between the possible chain nodes for this candidate
+            let links = match shared_links {
+                Some(ref shared_links) => shared_links.for_candidate(candidate),
+                None => Links::between(grid, nodes::get_nodes_for_candidate(grid, candidate)),
+            };
 
             // Find the X-Chains
-            for chain in aic::find_aics(grid, nodes) {
+            for chain in aic::find_aics(grid, &links) {
                 yield Step::XChain { chain };
             }
         }
     })
 }
 
-pub fn find_aics<'a, T: GridSize>(grid: &'a Grid<T>) -> impl Iterator<Item = Step<T>> + 'a {
+pub fn find_aics<'a, T: GridSize>(grid: &'a Grid<T>, link_graph: Option<&'a LinkGraph<T>>) -> impl Iterator<Item = Step<T>> + 'a {
 
     GeneratorAdapter::of(#[coroutine] move || {
 
-        // Create the possible chain nodes
-        let mut nodes = nodes::get_value_nodes(grid);
-        nodes.append(&mut nodes::get_group_nodes(grid));
-
         // Find the AICs
-        for chain in aic::find_aics(grid, nodes) {
+        for chain in aic::find_aics(grid, &get_links(grid, link_graph, false)) {
             yield Step::Aic { chain };
         }
     })
 }
 
-pub fn find_als_aics<'a, T: GridSize>(grid: &'a Grid<T>) -> impl Iterator<Item = Step<T>> + 'a {
+pub fn find_als_aics<'a, T: GridSize>(grid: &'a Grid<T>, link_graph: Option<&'a LinkGraph<T>>) -> impl Iterator<Item = Step<T>> + 'a {
 
     GeneratorAdapter::of(#[coroutine] move || {
 
-        // Create the possible chain nodes
-        let mut nodes = nodes::get_value_nodes(grid);
-        nodes.append(&mut nodes::get_group_nodes(grid));
-        nodes.append(&mut nodes::get_als_nodes(grid));
-
         // Find the AICs
-        for chain in aic::find_aics(grid, nodes) {
+        for chain in aic::find_aics(grid, &get_links(grid, link_graph, true)) {
             yield Step::AlsAic { chain };
         }
     })
 }
 
-pub fn find_forcing_chains<'a, T: GridSize>(grid: &'a Grid<T>) -> impl Iterator<Item = Step<T>> + 'a {
+pub fn find_forcing_chains<'a, T: GridSize>(grid: &'a Grid<T>, link_graph: Option<&'a LinkGraph<T>>) -> impl Iterator<Item = Step<T>> + 'a {
 
     GeneratorAdapter::of(#[coroutine] move || {
 
-        // Create the possible chain nodes
-        let mut nodes = nodes::get_value_nodes(grid);
-        nodes.append(&mut nodes::get_group_nodes(grid));
-
         // Find the forcing chains
-        for chain in forcing::find_forcing_chains(grid, nodes) {
+        for chain in forcing::find_forcing_chains(grid, &get_links(grid, link_graph, false)) {
             yield Step::ForcingChain { chain };
         }
     })
 }
 
-pub fn find_als_forcing_chains<'a, T: GridSize>(grid: &'a Grid<T>) -> impl Iterator<Item = Step<T>> + 'a {
+pub fn find_als_forcing_chains<'a, T: GridSize>(grid: &'a Grid<T>, link_graph: Option<&'a LinkGraph<T>>) -> impl Iterator<Item = Step<T>> + 'a {
 
     GeneratorAdapter::of(#[coroutine] move || {
 
-        // Create the possible chain nodes
-        let mut nodes = nodes::get_value_nodes(grid);
-        nodes.append(&mut nodes::get_group_nodes(grid));
-        nodes.append(&mut nodes::get_als_nodes(grid));
-
         // Find the forcing chains
-        for chain in forcing::find_forcing_chains(grid, nodes) {
+        for chain in forcing::find_forcing_chains(grid, &get_links(grid, link_graph, true)) {
             yield Step::AlsForcingChain { chain };
         }
     })
 }
+
+/// Get the links between the possible chain nodes, from the shared graph if there is one.
+fn get_links<T: GridSize>(grid: &Grid<T>, link_graph: Option<&LinkGraph<T>>, include_als: bool) -> Links<T> {
+    match link_graph {
+        Some(link_graph) => link_graph.links(grid, include_als),
+        None => Links::for_grid(grid, include_als),
+    }
+}
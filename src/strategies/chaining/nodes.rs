@@ -2,11 +2,15 @@
 
 use grid::{Candidate, CellIdx, Grid, GridSize};
 use grid::cellset::CellSet;
+use strategies::annotations::{AnnotatedCandidate, CandidateRole, Link};
 
 use itertools::Itertools;
 use std::collections::HashSet;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
-#[derive(PartialEq, Eq, Clone, Hash)]
+#[derive(PartialEq, Eq, Clone, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(bound = ""))]
 pub enum ChainNode<T: GridSize> {
     Value { cell: CellIdx, value: Candidate },
     Group { cells: CellSet<T>, value: Candidate },
@@ -23,6 +27,62 @@ impl <T: GridSize> ChainNode<T> {
             ChainNode::Als { cells, value, .. } => format!("{}{}", value, grid.region_name(cells)),
         }
     }
+
+    /// Get all of the cells which make up a `ChainNode`
+    pub fn get_cells(&self) -> CellSet<T> {
+        match self {
+            ChainNode::Value { cell, .. } => CellSet::from_cell(*cell),
+            ChainNode::Group { cells, .. } => *cells,
+            ChainNode::Als { cells, .. } => *cells,
+        }
+    }
+
+    /// Get the value of a `ChainNode`
+    pub fn get_value(&self) -> Candidate {
+        match self {
+            ChainNode::Value { value, .. } => *value,
+            ChainNode::Group { value, .. } => *value,
+            ChainNode::Als { value, .. } => *value,
+        }
+    }
+
+    /// Get the cells of a `ChainNode` which hold its value
+    pub fn get_value_cells(&self) -> CellSet<T> {
+        match self {
+            ChainNode::Value { cell, .. } => CellSet::from_cell(*cell),
+            ChainNode::Group { cells, .. } => *cells,
+            ChainNode::Als { cells_with_value, .. } => *cells_with_value,
+        }
+    }
+}
+
+/// Get the highlighted candidates and links for a chain made up of the given sequence of nodes,
+/// each paired with whether its inference is negated.
+pub fn annotate_chain<'a, T: GridSize + 'a, I: IntoIterator<Item = (&'a ChainNode<T>, bool)>>(inferences: I) -> (Vec<AnnotatedCandidate>, Vec<Link<T>>) {
+
+    let (mut candidates, mut links) = (Vec::new(), Vec::new());
+    let mut previous: Option<(CellSet<T>, Candidate, bool)> = None;
+
+    for (node, negated) in inferences {
+        let (value_cells, value) = match node {
+            ChainNode::Value { cell, value } => (CellSet::from_cell(*cell), *value),
+            ChainNode::Group { cells, value } => (*cells, *value),
+            ChainNode::Als { cells_with_value, value, .. } => (*cells_with_value, *value),
+        };
+
+        let role = if negated { CandidateRole::Off } else { CandidateRole::On };
+        for cell in value_cells.iter() {
+            candidates.push(AnnotatedCandidate { cell, value, role });
+        }
+
+        // A link from a negated inference to a positive one is strong, and the reverse is weak.
+        if let Some((from_cells, from_value, from_negated)) = previous {
+            links.push(Link { from_cells, from_value, to_cells: value_cells, to_value: value, strong: from_negated });
+        }
+        previous = Some((value_cells, value, negated));
+    }
+
+    (candidates, links)
 }
 
 /// Determine if the two nodes are linked in such a way that the truth
@@ -57,32 +117,29 @@ pub fn is_linked_off_to_on<T: GridSize>(grid: &Grid<T>, start_node: &ChainNode<T
     }
 }
 
-/// Get all `Value` chain nodes for a given candidate from the given grid.
-pub fn get_value_nodes_for_candidate<T: GridSize>(grid: &Grid<T>, candidate: Candidate) -> Vec<ChainNode<T>> {
+/// Get all `Value` and `Group` chain nodes for a given candidate from the given grid.
+pub fn get_nodes_for_candidate<T: GridSize>(grid: &Grid<T>, candidate: Candidate) -> Vec<ChainNode<T>> {
 
-    let mut value_nodes = Vec::new();
+    let mut nodes = Vec::new();
+    let mut used_nodes = HashSet::new();
 
     for cell in grid.cells_with_candidate(candidate).iter() {
-        value_nodes.push(ChainNode::Value { cell, value: candidate });
+        nodes.push(ChainNode::Value { cell, value: candidate });
     }
 
-    value_nodes
-}
-
-/// Get all `Group` chain nodes for a given candidate from the given grid.
-pub fn get_group_nodes_for_candidate<T: GridSize>(grid: &Grid<T>, candidate: Candidate) -> Vec<ChainNode<T>> {
-
-    let mut group_nodes = Vec::new();
-
     for (region1, region2) in grid.all_regions().iter().tuple_combinations() {
         let intersection = region1 & region2;
         let cells_with_candidate = grid.cells_with_candidate_in_region(candidate, &intersection);
         if cells_with_candidate.len() > 1 {
-            group_nodes.push(ChainNode::Group { cells: cells_with_candidate, value: candidate });
+            let group_node = ChainNode::Group { cells: cells_with_candidate, value: candidate };
+            if !used_nodes.contains(&group_node) {
+                used_nodes.insert(group_node.clone());
+                nodes.push(group_node);
+            }
         }
     }
 
-    group_nodes
+    nodes
 }
 
 /// Get all `Value` chain nodes from the given grid.
@@ -132,7 +189,7 @@ pub fn get_als_nodes<T: GridSize>(grid: &Grid<T>) -> Vec<ChainNode<T>> {
                 let candidates = grid.all_candidates_from_region(&cells);
                 if candidates.len() == degree + 1 {
                     for value in candidates.iter() {
-                        let als_node = ChainNode::Als { cells: cells.clone(), value, cells_with_value: grid.cells_with_candidate_in_region(value, &cells) };
+                        let als_node = ChainNode::Als { cells, value, cells_with_value: grid.cells_with_candidate_in_region(value, &cells) };
                         if !used_nodes.contains(&als_node) {
                             used_nodes.insert(als_node.clone());
                             als_nodes.push(als_node);
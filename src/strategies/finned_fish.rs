@@ -49,14 +49,14 @@ fn find_finned_fish<'a, T: GridSize>(grid: &'a Grid<T>, degree: usize, value: us
 
             // Iterate over all possible choices of covers that leave fins and check for eliminations.
             let num_fins = cover_sets.len() - degree;
-            let full_cover = CellSet::union(&cover_sets) & &candidate_positions;
+            let full_cover = CellSet::union(&cover_sets) & candidate_positions;
             if num_fins > 0 {
                 for ex_covers in cover_sets.into_iter().combinations(num_fins) {
                     let uncovered = CellSet::union(&ex_covers);
-                    let cover_union = &full_cover & !(&uncovered);
-                    let fins = &base_union & &uncovered;
-                    if !(grid.common_neighbours(&fins) & &cover_union & !(&base_union)).is_empty() {
-                        yield Step::FinnedFish { degree, base_type, base: base_union.clone(), cover: cover_union, fins, value };
+                    let cover_union = full_cover & !(&uncovered);
+                    let fins = base_union & uncovered;
+                    if !(grid.common_neighbours(&fins) & cover_union & !(&base_union)).is_empty() {
+                        yield Step::FinnedFish { degree, base_type, base: base_union, cover: cover_union, fins, value };
                     }
                 }
             }
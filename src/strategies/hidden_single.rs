@@ -19,13 +19,13 @@ pub fn find<'a, T: GridSize>(grid: &'a Grid<T>) -> impl Iterator<Item = Step<T>>
 
                 // There might be no place for this value, which is a contradiction. Check.
                 if cells.len() == 0 {
-                    yield Step::NoPlaceForCandidateInRegion { region: region.clone(), value: val};
+                    yield Step::NoPlaceForCandidateInRegion { region: *region, value: val};
                 }
 
                 // Otherwise check for a hidden single deduction.
                 if cells.len() == 1 {
                     let cell_idx = cells.first().unwrap();
-                    yield Step::HiddenSingle { region: region.clone(), cell: cell_idx, value: val };
+                    yield Step::HiddenSingle { region: *region, cell: cell_idx, value: val };
                 }
             }
         }
@@ -26,7 +26,7 @@ pub fn find_with_degree<'a, T: GridSize>(grid: &'a Grid<T>, degree: usize) -> im
                 // Check if the candidates appear in the right number of cells and if any eliminations will occur.
                 if cells.len() == degree {
                     if cells.iter().any(|cell| !(grid.candidates(cell) & !candidates).is_empty()) {
-                        yield Step::HiddenSubset { region: region.clone(), cells: cells.clone(), values: candidates };
+                        yield Step::HiddenSubset { region: *region, cells, values: candidates };
                     }
                 }
             }
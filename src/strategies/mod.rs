@@ -1,5 +1,6 @@
 //! Definitions of solving strategies for Sudoku puzzles.
 
+mod annotations;
 mod full_house;
 mod hidden_single;
 mod naked_single;
@@ -23,8 +24,18 @@ use strategies::chaining::Aic;
 use strategies::chaining::ForcingChain;
 use strategies::xy_chain::XYChain;
 
+use std::fmt;
+use std::str::FromStr;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+pub use self::annotations::{AnnotatedCandidate, CandidateRole, Link, StepAnnotations};
+pub use self::chaining::LinkGraph;
+
 /// The different types of deduction that can be made on a grid.
-#[derive(Clone)]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Deduction {
     /// Indicates that the given value can be placed in the cell at the given index.
     Placement(CellIdx, usize),
@@ -45,6 +56,8 @@ impl Deduction {
 }
 
 /// A step to be taken in the process of solving a given grid.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(bound = ""))]
 pub enum Step<T: GridSize> {
     NoCandidatesForCell { cell: CellIdx },
     NoPlaceForCandidateInRegion { region: CellSet<T>, value: usize},
@@ -70,7 +83,7 @@ pub enum Step<T: GridSize> {
 }
 
 /// The different strategies available to the solver.
-#[derive(PartialEq, Eq, Clone, Copy)]
+#[derive(PartialEq, Eq, Clone, Copy, Hash, Debug)]
 pub enum Strategy {
     FullHouse,
     HiddenSingle,
@@ -127,6 +140,17 @@ impl Strategy {
 
     /// Find a step arising from the chosen strategy.
     pub fn find_steps<'a, T: GridSize>(&self, grid: &'a Grid<T>) -> Box<dyn Iterator<Item = Step<T>> + 'a> {
+        self.find_steps_sharing(grid, None)
+    }
+
+    /// Find a step arising from the chosen strategy, taking the links for any chains from the
+    /// given graph, which is shared between strategies and kept up to date as the grid changes.
+    pub fn find_steps_with_links<'a, T: GridSize>(&self, grid: &'a Grid<T>, link_graph: &'a LinkGraph<T>) -> Box<dyn Iterator<Item = Step<T>> + 'a> {
+        self.find_steps_sharing(grid, Some(link_graph))
+    }
+
+    /// Find a step arising from the chosen strategy, sharing the links for chains if there's a graph.
+    fn find_steps_sharing<'a, T: GridSize>(&self, grid: &'a Grid<T>, link_graph: Option<&'a LinkGraph<T>>) -> Box<dyn Iterator<Item = Step<T>> + 'a> {
         match *self {
             Strategy::FullHouse => Box::new(full_house::find(&grid)),
             Strategy::HiddenSingle => Box::new(hidden_single::find(&grid)),
@@ -140,17 +164,79 @@ impl Strategy {
             Strategy::XYZWing => Box::new(xyz_wing::find(&grid)),
             Strategy::WWing => Box::new(w_wing::find(&grid)),
             Strategy::WXYZWing => Box::new(wxyz_wing::find(&grid)),
-            Strategy::XChain => Box::new(chaining::find_xchains(&grid)),
+            Strategy::XChain => Box::new(chaining::find_xchains(&grid, link_graph)),
             Strategy::XYChain => Box::new(xy_chain::find(&grid)),
-            Strategy::Aic => Box::new(chaining::find_aics(&grid)),
-            Strategy::AlsAic => Box::new(chaining::find_als_aics(&grid)),
-            Strategy::ForcingChain => Box::new(chaining::find_forcing_chains(&grid)),
-            Strategy::AlsForcingChain => Box::new(chaining::find_als_forcing_chains(&grid)),
+            Strategy::Aic => Box::new(chaining::find_aics(&grid, link_graph)),
+            Strategy::AlsAic => Box::new(chaining::find_als_aics(&grid, link_graph)),
+            Strategy::ForcingChain => Box::new(chaining::find_forcing_chains(&grid, link_graph)),
+            Strategy::AlsForcingChain => Box::new(chaining::find_als_forcing_chains(&grid, link_graph)),
             Strategy::Msls => Box::new(msls::find(&grid)),
         }
     }
 }
 
+/// Strategies are written in kebab-case, with the degree appended for those strategies that have
+/// one - for example `hidden-single`, `naked-subset-3` or `finned-fish-2`.
+impl fmt::Display for Strategy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Strategy::FullHouse => write!(f, "full-house"),
+            Strategy::HiddenSingle => write!(f, "hidden-single"),
+            Strategy::NakedSingle => write!(f, "naked-single"),
+            Strategy::BoxLine => write!(f, "box-line"),
+            Strategy::HiddenSubset(sz) => write!(f, "hidden-subset-{}", sz),
+            Strategy::NakedSubset(sz) => write!(f, "naked-subset-{}", sz),
+            Strategy::Fish(sz) => write!(f, "fish-{}", sz),
+            Strategy::FinnedFish(sz) => write!(f, "finned-fish-{}", sz),
+            Strategy::XYWing => write!(f, "xy-wing"),
+            Strategy::XYZWing => write!(f, "xyz-wing"),
+            Strategy::WWing => write!(f, "w-wing"),
+            Strategy::WXYZWing => write!(f, "wxyz-wing"),
+            Strategy::XChain => write!(f, "x-chain"),
+            Strategy::XYChain => write!(f, "xy-chain"),
+            Strategy::Aic => write!(f, "aic"),
+            Strategy::AlsAic => write!(f, "als-aic"),
+            Strategy::ForcingChain => write!(f, "forcing-chain"),
+            Strategy::AlsForcingChain => write!(f, "als-forcing-chain"),
+            Strategy::Msls => write!(f, "msls"),
+        }
+    }
+}
+
+impl FromStr for Strategy {
+
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Strategy, String> {
+        let (name, degree) = match input.rfind('-') {
+            Some(idx) if input[idx + 1..].parse::<usize>().is_ok() => (&input[..idx], input[idx + 1..].parse().ok()),
+            _ => (input, None),
+        };
+        match (name, degree) {
+            ("full-house", None) => Ok(Strategy::FullHouse),
+            ("hidden-single", None) => Ok(Strategy::HiddenSingle),
+            ("naked-single", None) => Ok(Strategy::NakedSingle),
+            ("box-line", None) => Ok(Strategy::BoxLine),
+            ("hidden-subset", Some(sz)) => Ok(Strategy::HiddenSubset(sz)),
+            ("naked-subset", Some(sz)) => Ok(Strategy::NakedSubset(sz)),
+            ("fish", Some(sz)) => Ok(Strategy::Fish(sz)),
+            ("finned-fish", Some(sz)) => Ok(Strategy::FinnedFish(sz)),
+            ("xy-wing", None) => Ok(Strategy::XYWing),
+            ("xyz-wing", None) => Ok(Strategy::XYZWing),
+            ("w-wing", None) => Ok(Strategy::WWing),
+            ("wxyz-wing", None) => Ok(Strategy::WXYZWing),
+            ("x-chain", None) => Ok(Strategy::XChain),
+            ("xy-chain", None) => Ok(Strategy::XYChain),
+            ("aic", None) => Ok(Strategy::Aic),
+            ("als-aic", None) => Ok(Strategy::AlsAic),
+            ("forcing-chain", None) => Ok(Strategy::ForcingChain),
+            ("als-forcing-chain", None) => Ok(Strategy::AlsForcingChain),
+            ("msls", None) => Ok(Strategy::Msls),
+            _ => Err(format!("Unknown strategy '{}'", input)),
+        }
+    }
+}
+
 impl <T: GridSize> Step<T> {
 
     /// Find the deductions given by the step.
@@ -206,4 +292,323 @@ impl <T: GridSize> Step<T> {
             ref msls @ Step::Msls { .. } => format!("{}", msls::get_description(grid, msls)),
         }
     }
+
+    /// Get a structured description of the step, listing the cells, candidates and links which make
+    /// up its pattern, along with the deductions that it makes.
+    pub fn annotations(&self, grid: &Grid<T>) -> StepAnnotations<T> {
+        annotations::get_annotations(grid, self)
+    }
+
+    /// Get the strategy which finds this kind of step.
+    pub fn get_strategy(&self) -> Strategy {
+        match self {
+            Step::NoCandidatesForCell { .. } => Strategy::NakedSingle,
+            Step::NoPlaceForCandidateInRegion { .. } => Strategy::HiddenSingle,
+            Step::FullHouse { .. } => Strategy::FullHouse,
+            Step::HiddenSingle { .. } => Strategy::HiddenSingle,
+            Step::NakedSingle { .. } => Strategy::NakedSingle,
+            Step::BoxLine { .. } => Strategy::BoxLine,
+            Step::HiddenSubset { cells, .. } => Strategy::HiddenSubset(cells.len()),
+            Step::NakedSubset { cells, .. } => Strategy::NakedSubset(cells.len()),
+            Step::Fish { degree, .. } => Strategy::Fish(*degree),
+            Step::FinnedFish { degree, .. } => Strategy::FinnedFish(*degree),
+            Step::XYWing { .. } => Strategy::XYWing,
+            Step::XYZWing { .. } => Strategy::XYZWing,
+            Step::WWing { .. } => Strategy::WWing,
+            Step::WXYZWing { .. } => Strategy::WXYZWing,
+            Step::XChain { .. } => Strategy::XChain,
+            Step::XYChain { .. } => Strategy::XYChain,
+            Step::Aic { .. } => Strategy::Aic,
+            Step::AlsAic { .. } => Strategy::AlsAic,
+            Step::ForcingChain { .. } => Strategy::ForcingChain,
+            Step::AlsForcingChain { .. } => Strategy::AlsForcingChain,
+            Step::Msls { .. } => Strategy::Msls,
+        }
+    }
+
+    /// Get the name of the technique used by the step.
+    pub fn get_name(&self) -> String {
+        match self {
+            Step::NoCandidatesForCell { .. } => "Contradiction".to_string(),
+            Step::NoPlaceForCandidateInRegion { .. } => "Contradiction".to_string(),
+            Step::FullHouse { .. } => "Full House".to_string(),
+            Step::HiddenSingle { .. } => "Hidden Single".to_string(),
+            Step::NakedSingle { .. } => "Naked Single".to_string(),
+            Step::BoxLine { .. } => "Box-line interaction".to_string(),
+            Step::HiddenSubset { cells, .. } => format!("Hidden {}", naked_subset::get_subset_name(cells.len())),
+            Step::NakedSubset { cells, .. } => format!("Naked {}", naked_subset::get_subset_name(cells.len())),
+            Step::Fish { degree, .. } => basic_fish::get_fish_name(*degree).to_string(),
+            Step::FinnedFish { degree, .. } => format!("Finned {}", basic_fish::get_fish_name(*degree)),
+            Step::XYWing { .. } => "XY-Wing".to_string(),
+            Step::XYZWing { .. } => "XYZ-Wing".to_string(),
+            Step::WWing { .. } => "W-Wing".to_string(),
+            Step::WXYZWing { .. } => "WXYZ-Wing".to_string(),
+            Step::XChain { .. } => "X-Chain".to_string(),
+            Step::XYChain { .. } => "XY-Chain".to_string(),
+            Step::Aic { .. } => "AIC".to_string(),
+            Step::AlsAic { .. } => "ALS AIC".to_string(),
+            Step::ForcingChain { .. } => "Forcing Chain".to_string(),
+            Step::AlsForcingChain { .. } => "ALS Forcing Chain".to_string(),
+            Step::Msls { .. } => "MSLS".to_string(),
+        }
+    }
+
+    /// Get the cells which make up the pattern used by the step.
+    pub fn get_cells(&self, grid: &Grid<T>) -> CellSet<T> {
+        match self {
+            Step::NoCandidatesForCell { cell } => CellSet::from_cell(*cell),
+            Step::NoPlaceForCandidateInRegion { region, .. } => grid.empty_cells_in_region(region),
+            Step::FullHouse { cell, .. } => CellSet::from_cell(*cell),
+            Step::HiddenSingle { cell, .. } => CellSet::from_cell(*cell),
+            Step::NakedSingle { cell, .. } => CellSet::from_cell(*cell),
+            Step::BoxLine { region, value, .. } => grid.cells_with_candidate_in_region(*value, region),
+            Step::HiddenSubset { cells, .. } => *cells,
+            Step::NakedSubset { cells, .. } => *cells,
+            Step::Fish { base, .. } => *base,
+            Step::FinnedFish { base, .. } => *base,
+            Step::XYWing { pivot, pincer1, pincer2, .. } => CellSet::from_cells(vec![*pivot, *pincer1, *pincer2]),
+            Step::XYZWing { pivot, pincer1, pincer2, .. } => CellSet::from_cells(vec![*pivot, *pincer1, *pincer2]),
+            Step::WWing { pincer1, pincer2, .. } => CellSet::from_cells(vec![*pincer1, *pincer2]),
+            Step::WXYZWing { cells, .. } => *cells,
+            Step::XChain { chain } => chaining::get_aic_cells(chain),
+            xy_chain @ Step::XYChain { .. } => xy_chain::get_cells(xy_chain),
+            Step::Aic { chain } => chaining::get_aic_cells(chain),
+            Step::AlsAic { chain } => chaining::get_aic_cells(chain),
+            Step::ForcingChain { chain } => chaining::get_forcing_chain_cells(chain),
+            Step::AlsForcingChain { chain } => chaining::get_forcing_chain_cells(chain),
+            Step::Msls { base, .. } => grid.empty_cells_in_region(&CellSet::union(base)),
+        }
+    }
+
+    /// Get the regions in which the pattern used by the step lies. Patterns which are not confined
+    /// to particular regions, such as wings and chains, return no regions.
+    pub fn get_regions(&self, grid: &Grid<T>) -> Vec<CellSet<T>> {
+        match self {
+            Step::NoPlaceForCandidateInRegion { region, .. } => vec![*region],
+            Step::FullHouse { region, .. } => vec![*region],
+            Step::HiddenSingle { region, .. } => vec![*region],
+            Step::BoxLine { region, .. } => vec![*region],
+            Step::HiddenSubset { region, .. } => vec![*region],
+            Step::NakedSubset { region, .. } => vec![*region],
+            Step::Fish { base_type, base, cover, .. } | Step::FinnedFish { base_type, base, cover, .. } => {
+                let mut regions = basic_fish::get_base_regions(grid, *base_type, base);
+                regions.append(&mut basic_fish::get_cover_regions(grid, *base_type, cover));
+                regions
+            },
+            Step::WWing { region, .. } => vec![*region],
+            Step::Msls { base, .. } => base.clone(),
+            _ => vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::fs;
+    use generator;
+    use generator::{BruteForce, Symmetry};
+    use generator::brute_force::BruteForceSolver;
+    use grid::transform::Transform;
+    use grid::variants::{classic_from_clues, classic_from_pencilmarks, empty_classic, Grid9};
+    use solver;
+    use super::*;
+
+    /// A position from `strategy_fixtures.txt`, together with what should be found there.
+    struct Fixture {
+        strategy: Strategy,
+        step: String,
+        deductions: Vec<String>,
+        absent: Vec<Strategy>,
+        grid: Grid<Grid9>,
+    }
+
+    fn read_fixtures() -> Vec<Fixture> {
+        fixture_blocks().into_iter().filter(|block| block.starts_with("strategy:")).map(|block| {
+            let field = |name: &str| block_field(&block, name);
+            let list = |name: &str| field(name).split(',').map(|item| item.trim()).filter(|item| !item.is_empty()).map(|item| item.to_string()).collect::<Vec<_>>();
+
+            // Descriptions of chains carry on over indented lines.
+            let step: Vec<&str> = block.lines()
+                .skip_while(|line| !line.starts_with("step: "))
+                .enumerate()
+                .take_while(|&(idx, line)| idx == 0 || line.starts_with(' '))
+                .map(|(idx, line)| if idx == 0 { &line["step: ".len()..] } else { line })
+                .collect();
+
+            Fixture {
+                strategy: field("strategy:").parse().unwrap(),
+                step: step.join("\n"),
+                deductions: list("deductions:"),
+                absent: list("absent:").iter().map(|name| name.parse().unwrap()).collect(),
+                grid: read_position(&block),
+            }
+        }).collect()
+    }
+
+    /// A position from `strategy_fixtures.txt` where a pattern for the strategy is only nearly
+    /// there, together with the elimination which completes it.
+    struct NearMiss {
+        strategy: Strategy,
+        restored: Deduction,
+        grid: Grid<Grid9>,
+    }
+
+    fn read_near_misses() -> Vec<NearMiss> {
+        fixture_blocks().into_iter().filter(|block| block.starts_with("near-miss:")).map(|block| {
+            let grid = read_position(&block);
+            let restored = grid.empty_cells().iter()
+                .flat_map(|cell| grid.candidates(cell).iter().map(move |value| Deduction::Elimination(cell, value)))
+                .find(|deduction| describe_deduction(&grid, deduction) == block_field(&block, "restored:"))
+                .unwrap();
+            NearMiss { strategy: block_field(&block, "near-miss:").parse().unwrap(), restored, grid }
+        }).collect()
+    }
+
+    fn fixture_blocks() -> Vec<String> {
+        let contents = fs::read_to_string("strategy_fixtures.txt").unwrap();
+        contents.split("\n\n").filter(|block| !block.starts_with("//")).map(|block| block.to_string()).collect()
+    }
+
+    fn block_field<'a>(block: &'a str, name: &str) -> &'a str {
+        block.lines().find(|line| line.starts_with(name)).map(|line| line[name.len()..].trim()).unwrap()
+    }
+
+    fn read_position(block: &str) -> Grid<Grid9> {
+        let pencilmarks: Vec<&str> = block.lines().filter(|line| line.starts_with('+') || line.starts_with('|')).collect();
+        classic_from_pencilmarks(pencilmarks.join("\n")).unwrap()
+    }
+
+    /// The first step found by the strategy which makes any deductions.
+    fn first_step<T: GridSize>(strategy: Strategy, grid: &Grid<T>) -> Option<(Step<T>, Vec<Deduction>)> {
+        strategy.find_steps(grid)
+            .map(|step| { let deductions = step.get_deductions(grid); (step, deductions) })
+            .find(|(_, deductions)| !deductions.is_empty())
+    }
+
+    fn describe_deduction<T: GridSize>(grid: &Grid<T>, deduction: &Deduction) -> String {
+        match *deduction {
+            Deduction::Placement(cell, value) => format!("{}={}", grid.cell_name(cell), value),
+            Deduction::Elimination(cell, value) => format!("{}-{}", grid.cell_name(cell), value),
+            Deduction::Contradiction => "contradiction".to_string(),
+        }
+    }
+
+    /// Check each deduction that each of the strategies first finds against the solution.
+    fn check_against_solution<T: GridSize>(grid: &Grid<T>, strategies: &[Strategy], solution: &[usize]) {
+        for &strategy in strategies {
+            for deduction in first_step(strategy, grid).map_or(vec![], |(_, deductions)| deductions) {
+                assert!(solver::is_sound(&deduction, solution), "{} made the unsound deduction {}\n{}", strategy, describe_deduction(grid, &deduction), grid.to_pencilmark_string());
+            }
+        }
+    }
+
+    #[test]
+    fn test_strategy_fixtures() {
+        let fixtures = read_fixtures();
+        assert!(ALL_STRATEGIES.iter().all(|strategy| fixtures.iter().any(|fixture| fixture.strategy == *strategy)));
+
+        for fixture in &fixtures {
+            let (step, deductions) = first_step(fixture.strategy, &fixture.grid).unwrap_or_else(|| panic!("{} found nothing", fixture.strategy));
+            assert_eq!(step.get_strategy(), fixture.strategy);
+            assert_eq!(step.get_description(&fixture.grid), fixture.step);
+            assert_eq!(deductions.iter().map(|deduction| describe_deduction(&fixture.grid, deduction)).collect::<Vec<_>>(), fixture.deductions);
+            for &strategy in &fixture.absent {
+                assert!(first_step(strategy, &fixture.grid).is_none(), "{} fired in the fixture for {}", strategy, fixture.strategy);
+            }
+        }
+    }
+
+    #[test]
+    fn test_near_misses() {
+        let near_misses = read_near_misses();
+        for strategy in &[Strategy::Fish(2), Strategy::FinnedFish(2), Strategy::XYWing, Strategy::XChain, Strategy::Msls] {
+            assert!(near_misses.iter().any(|near_miss| near_miss.strategy == *strategy), "no near miss for {}", strategy);
+        }
+
+        for near_miss in near_misses {
+            assert!(first_step(near_miss.strategy, &near_miss.grid).is_none(), "{} fired in its near miss", near_miss.strategy);
+            let mut grid = near_miss.grid.clone();
+            grid.apply_deduction(near_miss.restored);
+            assert!(first_step(near_miss.strategy, &grid).is_some(), "{} found nothing once its near miss was restored", near_miss.strategy);
+        }
+    }
+
+    /// The candidates and links annotated on the first step found by the strategy in its fixture,
+    /// written as 1r2c3 for a candidate and with == or -- for a strong or weak link.
+    fn fixture_annotations(strategy: Strategy) -> (Vec<String>, Vec<String>) {
+        let fixture = read_fixtures().into_iter().find(|fixture| fixture.strategy == strategy).unwrap();
+        let (step, deductions) = first_step(strategy, &fixture.grid).unwrap();
+        let annotations = step.annotations(&fixture.grid);
+        assert_eq!(annotations.cells, step.get_cells(&fixture.grid));
+        assert_eq!(format!("{:?}", annotations.deductions), format!("{:?}", deductions));
+
+        let names = |cells: &CellSet<Grid9>| cells.iter().map(|cell| fixture.grid.cell_name(cell)).collect::<Vec<_>>().join(",");
+        let candidates = annotations.candidates.iter()
+            .map(|candidate| format!("{}{} {:?}", candidate.value, fixture.grid.cell_name(candidate.cell), candidate.role))
+            .collect();
+        let links = annotations.links.iter()
+            .map(|link| format!("{}{} {} {}{}", link.from_value, names(&link.from_cells), if link.strong { "==" } else { "--" }, link.to_value, names(&link.to_cells)))
+            .collect();
+        (candidates, links)
+    }
+
+    #[test]
+    fn test_step_annotations() {
+        let (candidates, links) = fixture_annotations(Strategy::Fish(2));
+        assert_eq!(candidates, vec!["1r2c5 Pattern", "1r2c9 Pattern", "1r6c5 Pattern", "1r6c9 Pattern"]);
+        assert!(links.is_empty());
+
+        let (candidates, links) = fixture_annotations(Strategy::FinnedFish(2));
+        assert_eq!(candidates, vec!["4r4c3 Pattern", "4r4c6 Pattern", "4r7c3 Pattern", "4r7c6 Pattern", "4r4c4 Fin", "4r4c5 Fin"]);
+        assert!(links.is_empty());
+
+        let (candidates, links) = fixture_annotations(Strategy::NakedSubset(2));
+        assert_eq!(candidates, vec!["4r2c3 Pattern", "4r3c3 Pattern", "8r2c3 Pattern", "8r3c3 Pattern"]);
+        assert!(links.is_empty());
+
+        let (candidates, links) = fixture_annotations(Strategy::XChain);
+        assert_eq!(candidates, vec!["8r6c9 Off", "8r7c9 On", "8r7c1 Off", "8r9c2 On"]);
+        assert_eq!(links, vec!["8r6c9 == 8r7c9", "8r7c9 -- 8r7c1", "8r7c1 == 8r9c2"]);
+
+        let (candidates, links) = fixture_annotations(Strategy::XYChain);
+        assert_eq!(candidates, vec!["9r2c1 Off", "1r2c1 On", "1r2c6 Off", "3r2c6 On", "3r2c5 Off", "6r2c5 On", "6r3c4 Off", "9r3c4 On"]);
+        assert_eq!(links, vec!["9r2c1 == 1r2c1", "1r2c1 -- 1r2c6", "1r2c6 == 3r2c6", "3r2c6 -- 3r2c5", "3r2c5 == 6r2c5", "6r2c5 -- 6r3c4", "6r3c4 == 9r3c4"]);
+    }
+
+    #[test]
+    fn test_fixtures_under_random_symmetries() {
+        let mut rng = generator::seeded_rng(7);
+        for fixture in read_fixtures() {
+            // A pattern is still there once the grid is rearranged and relabelled, and nothing new
+            // appears.
+            let grid = Transform::random::<Grid9, _>(&mut rng).apply_to_grid(&fixture.grid);
+            assert!(first_step(fixture.strategy, &grid).is_some(), "{} found nothing once transformed", fixture.strategy);
+            assert!(fixture.absent.iter().all(|&strategy| first_step(strategy, &grid).is_none()));
+
+            let solution = BruteForceSolver::for_starting_grid(&grid).random_solution(&grid.to_clues()).unwrap();
+            check_against_solution(&grid, &[fixture.strategy], &solution);
+        }
+    }
+
+    #[test]
+    fn test_deductions_agree_with_brute_force() {
+        for clues in generator::generate_puzzles_on_empty_grid_with_symmetry(empty_classic(), Symmetry::Rotational, true, generator::seeded_rng(3)).take(3) {
+            let mut grid = classic_from_clues(&clues).unwrap();
+            let solution = BruteForceSolver::for_empty_grid(&empty_classic()).random_solution(&clues).unwrap();
+
+            // ALS chains, forcing chains and MSLS are slow to search for, so they're only checked
+            // when the solve needs them.
+            while let Some(idx) = ALL_STRATEGIES.iter().position(|&strategy| first_step(strategy, &grid).is_some()) {
+                let strategies: Vec<Strategy> = ALL_STRATEGIES.iter().enumerate()
+                    .filter(|&(other, strategy)| other <= idx || !matches!(strategy, Strategy::AlsAic | Strategy::ForcingChain | Strategy::AlsForcingChain | Strategy::Msls))
+                    .map(|(_, &strategy)| strategy)
+                    .collect();
+                check_against_solution(&grid, &strategies, &solution);
+                for deduction in first_step(ALL_STRATEGIES[idx], &grid).unwrap().1 {
+                    grid.apply_deduction(deduction);
+                }
+            }
+        }
+    }
 }
@@ -32,7 +32,7 @@ fn find_msls<'a, T: GridSize>(grid: &'a Grid<T>, base_degree: usize, digit_degre
         // Iterate over all possible choices for the base rows / columns, looking for an MSLS
         let all_bases = if base_type == Row { grid.rows() } else { grid.columns() };
         for base_sets in  all_bases.iter().combinations(base_degree) {
-            let base_union = CellSet::union(&base_sets.iter().map(|&x| x.clone()).collect::<Vec<_>>());
+            let base_union = CellSet::union(&base_sets.iter().map(|&x| *x).collect::<Vec<_>>());
 
             // Iterate over all subsets of possible digits
             for base_digits in (1..T::size() + 1).combinations(digit_degree) {
@@ -50,7 +50,7 @@ fn find_msls<'a, T: GridSize>(grid: &'a Grid<T>, base_degree: usize, digit_degre
                 for cover in all_covers {
 
                     // Work out where in the intersection of the base and the cover the digits can be placed
-                    let base_intersection = cover & &base_union;
+                    let base_intersection = cover & base_union;
                     let digits_to_place = digit_set.filter(|&d| grid.candidate_in_region(d, &base_intersection));
                     let available_cells = base_intersection.filter(|&cell| (grid.candidates(cell) & digit_set).len() > 0);
 
@@ -61,12 +61,12 @@ fn find_msls<'a, T: GridSize>(grid: &'a Grid<T>, base_degree: usize, digit_degre
                     } else if digits_to_place.len() == available_cells.len() {
                         single_cells |= &available_cells;
                         for digit in digits_to_place.iter() {
-                            cover_sets.push((cover.clone(), digit));
+                            cover_sets.push((*cover, digit));
                         }
                         placement_count += available_cells.len();
                     }else {
                         for digit in digits_to_place.iter() {
-                            cover_sets.push((cover.clone(), digit));
+                            cover_sets.push((*cover, digit));
                         }
                         placement_count += digits_to_place.len();
                     }
@@ -74,7 +74,7 @@ fn find_msls<'a, T: GridSize>(grid: &'a Grid<T>, base_degree: usize, digit_degre
 
                 // If the two counts are equal, it's MSLS town!
                 if missing_count == placement_count {
-                    yield Step::Msls { base: base_sets.iter().map(|&x| x.clone()).collect(), digits: digit_set, single_cells, cover: cover_sets };
+                    yield Step::Msls { base: base_sets.iter().map(|&x| *x).collect(), digits: digit_set, single_cells, cover: cover_sets };
                 }   
             }
         }
@@ -26,7 +26,7 @@ pub fn find_with_degree<'a, T: GridSize>(grid: &'a Grid<T>, degree: usize) -> im
                 // Check if the right number of candidates appear and if any eliminations will occur.
                 if candidates.len() == degree {
                     if grid.common_neighbours(&cells).iter().any(|cell| !(grid.candidates(cell) & candidates).is_empty()) {
-                        yield Step::NakedSubset { region: region.clone(), cells: cells.clone(), values: candidates };
+                        yield Step::NakedSubset { region: *region, cells, values: candidates };
                     }
                 }
             }
@@ -53,7 +53,7 @@ pub fn get_description<T: GridSize>(grid: &Grid<T>, naked_subset: &Step<T>) -> S
     }
 }
 
-fn get_subset_name<'a>(size: usize) -> &'a str {
+pub fn get_subset_name<'a>(size: usize) -> &'a str {
     match size {
         2 => "Pair",
         3 => "Triple",
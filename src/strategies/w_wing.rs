@@ -33,13 +33,13 @@ pub fn find<'a, T: GridSize>(grid: &'a Grid<T>) -> impl Iterator<Item = Step<T>>
                 // Check if the cells interact with the region in such a way that eliminations occur.
                 if !grid.value_placed_in_region(candidate1, &unseen_cells) && !grid.candidate_in_region(candidate1, &unseen_cells) {
                     if !grid.cells_with_candidate_in_region(candidate2, &common_neighbours).is_empty() {
-                        yield Step::WWing { pincer1: cell1, pincer2: cell2, region: region.clone(), covered_value: candidate1, eliminated_value: candidate2 };
+                        yield Step::WWing { pincer1: cell1, pincer2: cell2, region: *region, covered_value: candidate1, eliminated_value: candidate2 };
                     }
                 }
 
                 if !grid.value_placed_in_region(candidate2, &unseen_cells) && !grid.candidate_in_region(candidate2, &unseen_cells) {
                     if !grid.cells_with_candidate_in_region(candidate1, &common_neighbours).is_empty() {
-                        yield Step::WWing { pincer1: cell1, pincer2: cell2, region: region.clone(), covered_value: candidate2, eliminated_value: candidate1 };
+                        yield Step::WWing { pincer1: cell1, pincer2: cell2, region: *region, covered_value: candidate2, eliminated_value: candidate1 };
                     }
                 }
             }
@@ -27,10 +27,10 @@ pub fn find<'a, T: GridSize>(grid: &'a Grid<T>) -> impl Iterator<Item = Step<T>>
                     for fourth_cell in continuation_cells(grid, &empty_cells, &[first_cell, second_cell, third_cell]).iter() {
 
                         // Check whether this wing provides any deductions.
-                        let all_cells = CellSet::from_cells([first_cell, second_cell, third_cell, fourth_cell].iter().map(|x| *x));
+                        let all_cells = CellSet::from_cells([first_cell, second_cell, third_cell, fourth_cell].iter().copied());
                         for candidate in get_necessary_digits(grid, &all_cells).iter() {
                             if has_eliminations(grid, &all_cells, candidate) {
-                                yield Step::WXYZWing { cells: all_cells.clone(), value: candidate};
+                                yield Step::WXYZWing { cells: all_cells, value: candidate};
                             }
                         }
                     }
@@ -3,9 +3,13 @@
 use grid::{Candidate, CellIdx, Grid, GridSize};
 use grid::cellset::CellSet;
 use strategies::{Deduction, Step};
+use strategies::annotations::{AnnotatedCandidate, CandidateRole, Link};
 use utils::GeneratorAdapter;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct XYChainNode {
     cell: CellIdx,
     on_value: Candidate,
@@ -106,6 +110,34 @@ pub fn get_description<T: GridSize>(grid: &Grid<T>, xy_chain: &Step<T>) -> Strin
     }
 }
 
+/// Get the cells which make up the XY-Chain.
+pub fn get_cells<T: GridSize>(xy_chain: &Step<T>) -> CellSet<T> {
+    match xy_chain {
+        Step::XYChain { chain } => CellSet::from_cells(chain.iter().map(|node| node.cell)),
+        _ => unreachable!(),
+    }
+}
+
+/// Get the highlighted candidates and links which make up the XY-Chain.
+pub fn get_annotations<T: GridSize>(xy_chain: &Step<T>) -> (Vec<AnnotatedCandidate>, Vec<Link<T>>) {
+    match xy_chain {
+        Step::XYChain { chain } => {
+            let (mut candidates, mut links) = (Vec::new(), Vec::new());
+            for (idx, node) in chain.iter().enumerate() {
+                let cell = CellSet::from_cell(node.cell);
+                candidates.push(AnnotatedCandidate { cell: node.cell, value: node.off_value, role: CandidateRole::Off });
+                candidates.push(AnnotatedCandidate { cell: node.cell, value: node.on_value, role: CandidateRole::On });
+                links.push(Link { from_cells: cell, from_value: node.off_value, to_cells: cell, to_value: node.on_value, strong: true });
+                if let Some(next) = chain.get(idx + 1) {
+                    links.push(Link { from_cells: cell, from_value: node.on_value, to_cells: CellSet::from_cell(next.cell), to_value: next.off_value, strong: false });
+                }
+            }
+            (candidates, links)
+        },
+        _ => unreachable!(),
+    }
+}
+
 /// Check if there is a connection from one node to another in an XY-Chain
 fn is_connected<T: GridSize>(grid: &Grid<T>, from_node: &XYChainNode, to_node: &XYChainNode) -> bool {
     grid.neighbours(from_node.cell).contains(to_node.cell) && from_node.on_value == to_node.off_value
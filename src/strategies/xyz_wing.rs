@@ -68,5 +68,5 @@ fn first_pincers<T: GridSize>(grid: &Grid<T>, pivot: CellIdx) -> CellSet<T> {
 fn second_pincers<T: GridSize>(grid: &Grid<T>, pivot: CellIdx, pincer: CellIdx) -> CellSet<T> {
     grid.cells_with_n_candidates_in_region(2, &(grid.neighbours(pivot) & !grid.neighbours(pincer)))
         .filter(|&ix| (grid.candidates(ix) & grid.candidates(pivot)).len() == 2)
-        .filter(|&ix| (grid.candidates(ix) != grid.candidates(pincer)))
+        .filter(|&ix| grid.candidates(ix) != grid.candidates(pincer))
 }